@@ -0,0 +1,8 @@
+CREATE TABLE Entry (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id INTEGER NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER,
+    FOREIGN KEY (task_id) REFERENCES Task(id)
+);
+CREATE INDEX EntryByTask ON Entry(task_id);
@@ -0,0 +1 @@
+ALTER TABLE Task ADD COLUMN created_at INTEGER;
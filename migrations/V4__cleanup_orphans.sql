@@ -0,0 +1,25 @@
+-- Tasks and entries left behind by deleted projects and tasks are moved to a
+-- `recovered` project instead of being dropped, so no tracked time is lost.
+INSERT INTO Project (name)
+SELECT 'recovered'
+WHERE NOT EXISTS (SELECT 1 FROM Project WHERE name = 'recovered')
+  AND (EXISTS (SELECT 1 FROM Task WHERE project_id IS NULL OR project_id NOT IN (SELECT id FROM Project))
+    OR EXISTS (SELECT 1 FROM Entry WHERE task_id NOT IN (SELECT id FROM Task)));
+
+UPDATE Task SET project_id = (SELECT id FROM Project WHERE name = 'recovered')
+WHERE project_id IS NULL OR project_id NOT IN (SELECT id FROM Project);
+
+INSERT INTO Task (project_id, name)
+SELECT id, 'entries' FROM Project
+WHERE name = 'recovered'
+  AND EXISTS (SELECT 1 FROM Entry WHERE task_id NOT IN (SELECT id FROM Task));
+
+UPDATE Entry SET task_id = (
+    SELECT MAX(Task.id) FROM Task JOIN Project ON Project.id = Task.project_id
+    WHERE Project.name = 'recovered' AND Task.name = 'entries'
+)
+WHERE task_id NOT IN (SELECT id FROM Task);
+
+-- Links only, removing them loses no time
+DELETE FROM TaskTags WHERE task_id NOT IN (SELECT id FROM Task) OR tag_id NOT IN (SELECT id FROM Tag);
+DELETE FROM DefaultTags WHERE project_id NOT IN (SELECT id FROM Project) OR tag_id NOT IN (SELECT id FROM Tag);
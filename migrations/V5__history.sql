@@ -0,0 +1,6 @@
+CREATE TABLE History (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    description TEXT NOT NULL,
+    operations TEXT NOT NULL,
+    undone INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,2 @@
+ALTER TABLE Project ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE Task ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
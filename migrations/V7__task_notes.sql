@@ -0,0 +1 @@
+ALTER TABLE Task ADD COLUMN notes TEXT NOT NULL DEFAULT '';
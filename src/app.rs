@@ -1,11 +1,30 @@
+use crate::history::{self, Change};
+use crate::input::bindings::{BindingConfig, Bindings, Key};
 use crate::input::Prompt;
+use crate::report::DailyTotals;
 use crate::storage::Storage;
+use crate::ui::calendar::CalendarState;
 use crate::ui::explorer::ExplorerState;
-use crate::{model::*, storage};
+use crate::ui::reports::ReportState;
+use crate::ui::stats::StatsState;
+use crate::ui::timeline::TimelineState;
+use crate::{config, model::*, storage};
+use anyhow::Context;
+use chrono::{DateTime, Duration, Local, Weekday};
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{self, Instant};
+use tui::style::Color;
+
+/// How long info messages stay in the status bar.
+const INFO_TIMEOUT: time::Duration = time::Duration::from_secs(3);
+/// How long errors stay in the status bar.
+const ERROR_TIMEOUT: time::Duration = time::Duration::from_secs(6);
 
 pub struct App {
     pub settings: Settings,
+    pub bindings: Bindings,
 
     pub state: State,
 
@@ -13,26 +32,111 @@ pub struct App {
     pub repository: Repository,
 }
 
-#[derive(Default, Debug)]
-pub struct Settings {}
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub database: Option<PathBuf>,
+
+    pub date_format: String,
+    pub time_format: String,
+    #[serde(deserialize_with = "config::deserialize_weekday")]
+    pub week_start: Weekday,
+    /// Displayed durations are rounded to the nearest multiple of this many minutes.
+    pub rounding: u32,
+
+    pub theme: Theme,
+    pub keybindings: HashMap<String, BindingConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(deserialize_with = "config::deserialize_color")]
+    pub accent: Color,
+    #[serde(deserialize_with = "config::deserialize_color")]
+    pub running: Color,
+    #[serde(deserialize_with = "config::deserialize_color")]
+    pub error: Color,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            database: None,
+            date_format: "%-d %b %Y".to_string(),
+            time_format: "%H:%M".to_string(),
+            week_start: Weekday::Mon,
+            rounding: 0,
+            theme: Theme::default(),
+            keybindings: HashMap::new(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            accent: Color::Blue,
+            running: Color::Green,
+            error: Color::Red,
+        }
+    }
+}
+
+impl Settings {
+    pub fn round(&self, duration: Duration) -> Duration {
+        if self.rounding == 0 {
+            return duration;
+        }
+        let step = i64::from(self.rounding) * 60;
+        let seconds = (duration.num_seconds() + step / 2) / step * step;
+        Duration::seconds(seconds)
+    }
+}
 
 #[derive(Default)]
 pub struct State {
     pub focus: Pane,
     pub prompt_stack: Vec<Prompt>,
+    /// Keys pressed so far of an incomplete sequence.
+    pub pending_keys: Vec<Key>,
+    pub status: Option<Status>,
 
     pub explorer: ExplorerState,
+    pub reports: ReportState,
+    pub stats: StatsState,
+    pub calendar: CalendarState,
+    pub timeline: TimelineState,
+    /// Time spent per day, shared by the charts, the calendar and the explorer
+    /// sparklines.
+    pub daily: DailyTotals<ProjectId>,
+    /// Time spent per day on each tag, for the stats.
+    pub daily_tags: DailyTotals<String>,
+}
+
+/// Message shown at the bottom of the screen until it expires.
+pub struct Status {
+    pub message: String,
+    pub kind: StatusKind,
+    pub expires: Instant,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    Info,
+    Error,
 }
 
+#[derive(Default)]
 pub enum Pane {
+    #[default]
     ProjectExplorer,
     Main,
-}
-
-impl Default for Pane {
-    fn default() -> Self {
-        Self::ProjectExplorer
-    }
+    Entries,
+    Reports,
+    Stats,
+    Calendar,
+    Timeline,
 }
 
 #[derive(Default, Debug)]
@@ -40,12 +144,23 @@ pub struct Repository {
     pub tags: HashMap<TagId, Tag>,
     pub projects: HashMap<ProjectId, Project>,
     pub tasks: HashMap<TaskId, Task>,
+    pub entries: HashMap<EntryId, Entry>,
+
+    /// Entries of each task, ordered by start time.
+    pub task_entries: HashMap<TaskId, Vec<EntryId>>,
 }
 
 impl App {
-    pub fn new(settings: Settings, state: State, storage: Storage, repository: Repository) -> Self {
+    pub fn new(
+        settings: Settings,
+        bindings: Bindings,
+        state: State,
+        storage: Storage,
+        repository: Repository,
+    ) -> Self {
         App {
             settings,
+            bindings,
             state,
             storage,
             repository,
@@ -54,6 +169,12 @@ impl App {
 
     pub fn sync(&mut self) {
         self.state.explorer.sync(&self.repository);
+        self.state.daily = DailyTotals::projects(&self.repository);
+        self.state.daily_tags = DailyTotals::tags(&self.repository);
+        self.state.reports.invalidate();
+        if matches!(self.state.focus, Pane::Reports) {
+            self.state.reports.refresh(&self.repository, &self.settings);
+        }
     }
 
     pub fn update_focus(&mut self) {
@@ -76,6 +197,104 @@ impl App {
         self.state.prompt_stack.last()
     }
 
+    pub fn info<S: Into<String>>(&mut self, message: S) {
+        self.state.status = Some(Status {
+            message: message.into(),
+            kind: StatusKind::Info,
+            expires: Instant::now() + INFO_TIMEOUT,
+        });
+    }
+
+    /// Shows a recoverable error with its causes instead of exiting.
+    pub fn error(&mut self, error: &anyhow::Error) {
+        self.state.status = Some(Status {
+            message: format!("{:#}", error),
+            kind: StatusKind::Error,
+            expires: Instant::now() + ERROR_TIMEOUT,
+        });
+    }
+
+    /// The status message, unless it has expired.
+    pub fn status(&self) -> Option<&Status> {
+        self.state
+            .status
+            .as_ref()
+            .filter(|status| status.expires > Instant::now())
+    }
+
+    /// Time left before the status message expires and must be cleared.
+    pub fn status_timeout(&self) -> Option<time::Duration> {
+        self.status()
+            .map(|status| status.expires.saturating_duration_since(Instant::now()))
+    }
+
+    /// Applies a change and records it to be undone, all in one transaction,
+    /// then syncs the state derived from the repository.
+    pub fn execute(&mut self, change: Change) -> anyhow::Result<()> {
+        let transaction = self.storage.transaction()?;
+        change.write(&transaction)?;
+        change.record(transaction, &mut self.repository)?;
+        self.sync();
+        Ok(())
+    }
+
+    /// Reverts the last change, returning its description.
+    pub fn undo(&mut self) -> anyhow::Result<Option<String>> {
+        let transaction = self.storage.transaction()?;
+        match transaction.next_change(false)? {
+            Some((id, change)) => {
+                let inverse = change.inverse();
+                inverse
+                    .write(&transaction)
+                    .with_context(|| format!("Could not undo {}", change.description))?;
+                transaction.set_undone(id, true)?;
+                transaction.commit()?;
+                inverse.apply(&mut self.repository);
+                self.sync();
+                Ok(Some(change.description))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Applies again the last undone change, returning its description.
+    pub fn redo(&mut self) -> anyhow::Result<Option<String>> {
+        let transaction = self.storage.transaction()?;
+        match transaction.next_change(true)? {
+            Some((id, change)) => {
+                change
+                    .write(&transaction)
+                    .with_context(|| format!("Could not redo {}", change.description))?;
+                transaction.set_undone(id, false)?;
+                transaction.commit()?;
+                change.apply(&mut self.repository);
+                self.sync();
+                Ok(Some(change.description))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Starts a running entry on the given task, stopping any other running one.
+    pub fn start_timer(&mut self, task_id: TaskId) -> anyhow::Result<()> {
+        let transaction = self.storage.transaction()?;
+        let operations =
+            history::start_timer(&self.repository, &transaction, &task_id, Local::now())?;
+        Change::new("start timer", operations).record(transaction, &mut self.repository)?;
+        self.sync();
+        Ok(())
+    }
+
+    pub fn stop_timer(&mut self, now: DateTime<Local>) -> anyhow::Result<()> {
+        let transaction = self.storage.transaction()?;
+        let operations = history::stop_timer(&self.repository, &transaction, now)?
+            .into_iter()
+            .collect();
+        Change::new("stop timer", operations).record(transaction, &mut self.repository)?;
+        self.sync();
+        Ok(())
+    }
+
     pub fn awake_prompt(&mut self) {
         if let Some(last) = self.state.prompt_stack.last_mut() {
             last.awake(&self.repository);
@@ -88,16 +307,59 @@ impl Repository {
         self.tags.insert(tag.id, tag);
     }
 
+    pub fn rename_tag(&mut self, tag_id: &TagId, name: String) {
+        if let Some(tag) = self.tags.get_mut(tag_id) {
+            tag.name = name;
+        }
+    }
+
     pub fn remove_tag(&mut self, tag_id: &TagId) {
         self.tags.remove(tag_id);
+        for project in self.projects.values_mut() {
+            project.default_tags.retain(|id| id.ne(tag_id));
+        }
+        for task in self.tasks.values_mut() {
+            task.tags.retain(|id| id.ne(tag_id));
+        }
     }
 
     pub fn add_project(&mut self, project: Project) {
         self.projects.insert(project.id, project);
     }
 
+    pub fn rename_project(&mut self, project_id: &ProjectId, name: String) {
+        if let Some(project) = self.projects.get_mut(project_id) {
+            project.name = name;
+        }
+    }
+
+    pub fn set_project_archived(&mut self, project_id: &ProjectId, archived: bool) {
+        if let Some(project) = self.projects.get_mut(project_id) {
+            project.archived = archived;
+        }
+    }
+
+    /// Removes a project along with its tasks and their entries.
     pub fn remove_project(&mut self, project_id: &ProjectId) {
-        self.projects.remove(project_id);
+        if let Some(project) = self.projects.remove(project_id) {
+            for task_id in &project.tasks {
+                self.remove_task(task_id);
+            }
+        }
+    }
+
+    pub fn add_default_tag(&mut self, project_id: &ProjectId, tag_id: TagId) {
+        if let Some(project) = self.projects.get_mut(project_id) {
+            if !project.default_tags.contains(&tag_id) {
+                project.default_tags.push(tag_id);
+            }
+        }
+    }
+
+    pub fn remove_default_tag(&mut self, project_id: &ProjectId, tag_id: &TagId) {
+        if let Some(project) = self.projects.get_mut(project_id) {
+            project.default_tags.retain(|id| id.ne(tag_id));
+        }
     }
 
     pub fn add_task(&mut self, task: Task) {
@@ -107,7 +369,64 @@ impl Repository {
         self.tasks.insert(task.id, task);
     }
 
+    pub fn rename_task(&mut self, task_id: &TaskId, name: String) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.name = name;
+        }
+    }
+
+    pub fn set_task_notes(&mut self, task_id: &TaskId, notes: String) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.notes = notes;
+        }
+    }
+
+    pub fn set_task_archived(&mut self, task_id: &TaskId, archived: bool) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.archived = archived;
+        }
+    }
+
+    pub fn move_task(&mut self, task_id: &TaskId, project_id: ProjectId) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            if let Some(project) = self.projects.get_mut(&task.project_id) {
+                project.tasks.retain(|id| id.ne(task_id));
+            }
+            task.project_id = project_id;
+            if let Some(project) = self.projects.get_mut(&project_id) {
+                project.tasks.push(*task_id);
+            }
+        }
+    }
+
+    /// Tags to remove from and add to a task to replace the default tags of a
+    /// project with those of another. Tags shared by both are kept.
+    pub fn default_tags_swap(
+        &self,
+        task_id: &TaskId,
+        from: &ProjectId,
+        to: &ProjectId,
+    ) -> (Vec<TagId>, Vec<TagId>) {
+        let task = Task::from_id(task_id, self);
+        let from = &Project::from_id(from, self).default_tags;
+        let to = &Project::from_id(to, self).default_tags;
+        let removed = from
+            .iter()
+            .filter(|id| task.tags.contains(id) && !to.contains(id))
+            .cloned()
+            .collect();
+        let added = to
+            .iter()
+            .filter(|id| !task.tags.contains(id))
+            .cloned()
+            .collect();
+        (removed, added)
+    }
+
     pub fn remove_task(&mut self, task_id: &TaskId) {
+        for entry_id in self.task_entries.remove(task_id).unwrap_or_default() {
+            self.entries.remove(&entry_id);
+        }
         if let Some(task) = self.tasks.remove(task_id) {
             if let Some(project) = self.projects.get_mut(&task.project_id) {
                 let index = project
@@ -119,16 +438,76 @@ impl Repository {
             }
         }
     }
+
+    pub fn add_task_tag(&mut self, task_id: &TaskId, tag_id: TagId) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            if !task.tags.contains(&tag_id) {
+                task.tags.push(tag_id);
+            }
+        }
+    }
+
+    pub fn remove_task_tag(&mut self, task_id: &TaskId, tag_id: &TagId) {
+        if let Some(task) = self.tasks.get_mut(task_id) {
+            task.tags.retain(|id| id.ne(tag_id));
+        }
+    }
+
+    pub fn add_entry(&mut self, entry: Entry) {
+        let entries = self.task_entries.entry(entry.task_id).or_default();
+        let index = entries.partition_point(|id| self.entries[id].start <= entry.start);
+        entries.insert(index, entry.id);
+        self.entries.insert(entry.id, entry);
+    }
+
+    pub fn update_entry(&mut self, entry: Entry) {
+        self.remove_entry(&entry.id);
+        self.add_entry(entry);
+    }
+
+    pub fn remove_entry(&mut self, entry_id: &EntryId) {
+        if let Some(entry) = self.entries.remove(entry_id) {
+            if let Some(entries) = self.task_entries.get_mut(&entry.task_id) {
+                entries.retain(|id| id.ne(entry_id));
+            }
+        }
+    }
+
+    pub fn running_entry(&self) -> Option<&Entry> {
+        self.entries.values().find(|entry| entry.is_running())
+    }
+
+    pub fn task_entries(&self, task_id: &TaskId) -> Vec<&Entry> {
+        self.task_entries
+            .get(task_id)
+            .map(|entries| entries.iter().map(|id| &self.entries[id]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Start of the last entry of a task, or its creation without entries.
+    pub fn task_last_used(&self, task_id: &TaskId) -> Option<DateTime<Local>> {
+        match self.task_entries(task_id).last() {
+            Some(entry) => Some(entry.start),
+            None => self.tasks.get(task_id).and_then(|task| task.created_at),
+        }
+    }
+
+    pub fn task_time(&self, task_id: &TaskId, now: DateTime<Local>) -> Duration {
+        self.task_entries(task_id)
+            .iter()
+            .fold(Duration::zero(), |total, entry| total + entry.duration(now))
+    }
 }
 
-pub fn init() -> anyhow::Result<App> {
-    let settings = Settings::default();
+pub fn init(settings: Settings, database: &Path) -> anyhow::Result<App> {
+    let bindings =
+        Bindings::new(&settings.keybindings).context("Invalid keybindings in config file")?;
     let state = State::default();
-    let storage = storage::init_storage()?;
+    let storage = storage::init_storage(database)?;
     let repository = storage::load::load_repository(&storage)?;
 
-    let mut app = App::new(settings, state, storage, repository);
-    app.state.explorer.sync(&app.repository);
+    let mut app = App::new(settings, bindings, state, storage, repository);
+    app.sync();
 
     Ok(app)
 }
@@ -0,0 +1,521 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+
+use crate::app::App;
+use crate::config;
+use crate::history::{self, Change, Operation};
+use crate::model::*;
+use crate::prompts::count;
+use crate::report::{self, Grouping};
+use crate::storage::Transaction;
+use crate::ui::util::{format_date, format_duration, format_time};
+
+/// Version of the JSON documents printed with `--format json`, see `docs/json.md`.
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Parser)]
+#[command(name = "dt", version, about)]
+pub struct Cli {
+    /// Path of the database, overriding `DOING_TUI_DB` and the config file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+
+    /// Output format of read commands
+    #[arg(long, global = true, value_enum, default_value_t = Format::Text)]
+    pub format: Format,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start a timer on a task, stopping the running one
+    Start {
+        /// Task to start, as `<project>/<task>`
+        path: String,
+        /// Create the project and task if they don't exist
+        #[arg(short, long)]
+        create: bool,
+    },
+    /// Stop the running timer
+    Stop,
+    /// Show the running timer
+    Status,
+    /// Create a project, or a task with `<project>/<task>` and its project if
+    /// missing, failing if it already exists
+    Add { path: String },
+    /// Revert the last change made in the TUI
+    Undo,
+    /// Apply again the last undone change
+    Redo,
+    /// List entries recorded since a date
+    Log {
+        /// `today`, `yesterday`, `week`, `month` or a date formatted as YYYY-MM-DD
+        #[arg(short, long, default_value = "today")]
+        since: String,
+    },
+    /// List projects and the time spent on them
+    Projects,
+    /// List tasks and the time spent on them
+    Tasks {
+        /// Only list the tasks of this project
+        project: Option<String>,
+    },
+    /// Show the location of the config file
+    Config {
+        /// Print a commented config file with the default settings
+        #[arg(long)]
+        print_default: bool,
+    },
+    /// Summarize the time spent since a date
+    Report {
+        /// `today`, `yesterday`, `week`, `month` or a date formatted as YYYY-MM-DD
+        #[arg(short, long, default_value = "today")]
+        since: String,
+        #[arg(short, long, value_enum, default_value_t = Grouping::Project)]
+        by: Grouping,
+    },
+}
+
+#[derive(Serialize)]
+struct Document<T: Serialize> {
+    version: u32,
+    #[serde(flatten)]
+    data: T,
+}
+
+#[derive(Serialize)]
+struct EntryOutput<'a> {
+    #[serde(flatten)]
+    entry: &'a Entry,
+    project: &'a str,
+    task: &'a str,
+    duration: i64,
+}
+
+#[derive(Serialize)]
+struct ProjectOutput<'a> {
+    #[serde(flatten)]
+    project: &'a Project,
+    duration: i64,
+}
+
+#[derive(Serialize)]
+struct TaskOutput<'a> {
+    #[serde(flatten)]
+    task: &'a Task,
+    duration: i64,
+}
+
+#[derive(Serialize)]
+struct GroupOutput<'a> {
+    name: &'a str,
+    duration: i64,
+    share: f64,
+}
+
+pub fn run(app: &mut App, command: Command, format: Format) -> anyhow::Result<()> {
+    let now = Local::now();
+    match command {
+        Command::Config { .. } => unreachable!("Config is handled before loading the app"),
+        Command::Start { path, create } => {
+            let (project, task) = parse_path(&path)?;
+            let task = task.ok_or_else(|| anyhow!("Expected `<project>/<task>`"))?;
+            check_not_archived(app, project, task)?;
+            let transaction = app.storage.transaction()?;
+            let mut operations = Vec::new();
+            let task_id = if create {
+                find_or_create_task(app, &transaction, &mut operations, project, task)?
+            } else {
+                find_task(app, project, task)?
+            };
+            operations.extend(history::start_timer(
+                &app.repository,
+                &transaction,
+                &task_id,
+                now,
+            )?);
+            Change::new("start timer", operations).record(transaction, &mut app.repository)?;
+            println!("Started {}/{}", project, task);
+        }
+        Command::Stop => match app.repository.running_entry().cloned() {
+            Some(entry) => {
+                app.stop_timer(now)?;
+                println!(
+                    "Stopped {} after {}",
+                    entry_path(app, &entry),
+                    format_duration(&app.settings, entry.duration(now))
+                );
+            }
+            None => println!("No timer running"),
+        },
+        Command::Status => {
+            let running = app.repository.running_entry();
+            match format {
+                Format::Json => {
+                    #[derive(Serialize)]
+                    struct Status<'a> {
+                        running: Option<EntryOutput<'a>>,
+                    }
+                    print_json(Status {
+                        running: running.map(|entry| entry_output(app, entry, now)),
+                    })?;
+                }
+                Format::Text => match running {
+                    Some(entry) => println!(
+                        "{} {}",
+                        entry_path(app, entry),
+                        format_duration(&app.settings, entry.duration(now))
+                    ),
+                    None => println!("No timer running"),
+                },
+            }
+        }
+        Command::Add { path } => {
+            let (project, task) = parse_path(&path)?;
+            match task {
+                Some(task) => {
+                    if find_task(app, project, task).is_ok() {
+                        bail!("Task `{}/{}` already exists", project, task);
+                    }
+                    let transaction = app.storage.transaction()?;
+                    let mut operations = Vec::new();
+                    find_or_create_task(app, &transaction, &mut operations, project, task)?;
+                    Change::new("create task", operations)
+                        .record(transaction, &mut app.repository)?;
+                }
+                None => {
+                    if find_project(app, project).is_ok() {
+                        bail!("Project `{}` already exists", project);
+                    }
+                    let transaction = app.storage.transaction()?;
+                    let project = transaction.create_project(project.to_string())?;
+                    Change::new("create project", vec![Operation::InsertProject(project)])
+                        .record(transaction, &mut app.repository)?;
+                }
+            }
+        }
+        Command::Undo => match app.undo()? {
+            Some(description) => println!("Undid {}", description),
+            None => println!("Nothing to undo"),
+        },
+        Command::Redo => match app.redo()? {
+            Some(description) => println!("Redid {}", description),
+            None => println!("Nothing to redo"),
+        },
+        Command::Log { since } => {
+            let since = report::start_of_day(parse_day(app, &since)?);
+            let mut entries: Vec<&Entry> = app
+                .repository
+                .entries
+                .values()
+                .filter(|entry| entry.end.unwrap_or(now) >= since)
+                .collect();
+            entries.sort_by_key(|entry| entry.start);
+            match format {
+                Format::Json => {
+                    #[derive(Serialize)]
+                    struct Log<'a> {
+                        since: DateTime<Local>,
+                        entries: Vec<EntryOutput<'a>>,
+                    }
+                    print_json(Log {
+                        since,
+                        entries: entries
+                            .into_iter()
+                            .map(|entry| entry_output(app, entry, now))
+                            .collect(),
+                    })?;
+                }
+                Format::Text => {
+                    for entry in entries {
+                        println!(
+                            "{} {}  {}  {:>8}  {}",
+                            format_date(&app.settings, entry.start),
+                            format_time(&app.settings, entry.start),
+                            entry
+                                .end
+                                .map(|end| format_time(&app.settings, end))
+                                .unwrap_or_else(|| "now".to_string()),
+                            format_duration(&app.settings, entry.duration(now)),
+                            entry_path(app, entry)
+                        );
+                    }
+                }
+            }
+        }
+        Command::Projects => {
+            let mut projects: Vec<&Project> = app.repository.projects.values().collect();
+            projects.sort_by_key(|project| &project.name);
+            let projects: Vec<ProjectOutput> = projects
+                .into_iter()
+                .map(|project| ProjectOutput {
+                    project,
+                    duration: project
+                        .tasks
+                        .iter()
+                        .fold(Duration::zero(), |total, task_id| {
+                            total + app.repository.task_time(task_id, now)
+                        })
+                        .num_seconds(),
+                })
+                .collect();
+            match format {
+                Format::Json => {
+                    #[derive(Serialize)]
+                    struct Projects<'a> {
+                        projects: Vec<ProjectOutput<'a>>,
+                        tags: Vec<&'a Tag>,
+                    }
+                    let mut tags: Vec<&Tag> = app.repository.tags.values().collect();
+                    tags.sort_by_key(|tag| tag.id.0);
+                    print_json(Projects { projects, tags })?;
+                }
+                Format::Text => {
+                    for output in projects {
+                        println!(
+                            "{}  {}  {}{}",
+                            output.project.name,
+                            count(output.project.tasks.len(), "task", "tasks"),
+                            format_duration(&app.settings, Duration::seconds(output.duration)),
+                            archived_marker(output.project.archived)
+                        );
+                    }
+                }
+            }
+        }
+        Command::Tasks { project } => {
+            let project_id = project.map(|name| find_project(app, &name)).transpose()?;
+            let mut tasks: Vec<&Task> = app
+                .repository
+                .tasks
+                .values()
+                .filter(|task| project_id.is_none_or(|id| task.project_id == id))
+                .collect();
+            tasks.sort_by_cached_key(|task| {
+                (
+                    Project::from_id(&task.project_id, &app.repository)
+                        .name
+                        .clone(),
+                    task.name.clone(),
+                )
+            });
+            match format {
+                Format::Json => {
+                    #[derive(Serialize)]
+                    struct Tasks<'a> {
+                        tasks: Vec<TaskOutput<'a>>,
+                    }
+                    print_json(Tasks {
+                        tasks: tasks
+                            .into_iter()
+                            .map(|task| TaskOutput {
+                                task,
+                                duration: app.repository.task_time(&task.id, now).num_seconds(),
+                            })
+                            .collect(),
+                    })?;
+                }
+                Format::Text => {
+                    for task in tasks {
+                        let project = Project::from_id(&task.project_id, &app.repository);
+                        println!(
+                            "{}/{}  {}{}",
+                            project.name,
+                            task.name,
+                            format_duration(&app.settings, app.repository.task_time(&task.id, now)),
+                            archived_marker(task.archived)
+                        );
+                    }
+                }
+            }
+        }
+        Command::Report { since, by } => {
+            let since = report::start_of_day(parse_day(app, &since)?);
+            let report = report::aggregate(&app.repository, since, now, by, now);
+            match format {
+                Format::Json => {
+                    #[derive(Serialize)]
+                    struct Report<'a> {
+                        start: DateTime<Local>,
+                        end: DateTime<Local>,
+                        by: &'a str,
+                        total: i64,
+                        groups: Vec<GroupOutput<'a>>,
+                    }
+                    print_json(Report {
+                        start: report.start,
+                        end: report.end,
+                        by: report.grouping.name(),
+                        total: report.total.num_seconds(),
+                        groups: report
+                            .groups
+                            .iter()
+                            .map(|group| GroupOutput {
+                                name: &group.name,
+                                duration: group.duration.num_seconds(),
+                                share: report.share(group),
+                            })
+                            .collect(),
+                    })?;
+                }
+                Format::Text => {
+                    for group in &report.groups {
+                        println!(
+                            "{:>8}  {:>3.0}%  {}",
+                            format_duration(&app.settings, group.duration),
+                            report.share(group) * 100.0,
+                            group.name
+                        );
+                    }
+                    println!("{:>8}  total", format_duration(&app.settings, report.total));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs the commands which must work without valid settings or database.
+pub fn run_early(command: &Command) -> Option<anyhow::Result<()>> {
+    match command {
+        Command::Config { print_default } => {
+            if *print_default {
+                print!("{}", config::default_config());
+                Some(Ok(()))
+            } else {
+                Some(config::config_path().map(|path| println!("{}", path.display())))
+            }
+        }
+        _ => None,
+    }
+}
+
+fn print_json<T: Serialize>(data: T) -> anyhow::Result<()> {
+    let document = Document {
+        version: SCHEMA_VERSION,
+        data,
+    };
+    println!("{}", serde_json::to_string_pretty(&document)?);
+    Ok(())
+}
+
+fn parse_path(path: &str) -> anyhow::Result<(&str, Option<&str>)> {
+    let (project, task) = match path.split_once('/') {
+        Some((project, task)) => (project.trim(), Some(task.trim())),
+        None => (path.trim(), None),
+    };
+    if project.is_empty() || task.map(str::is_empty).unwrap_or(false) {
+        bail!("Invalid path `{}`", path);
+    }
+    Ok((project, task))
+}
+
+fn parse_day(app: &App, day: &str) -> anyhow::Result<NaiveDate> {
+    let today = Local::now().date_naive();
+    match day {
+        "today" => Ok(today),
+        "yesterday" => Ok(today - Duration::days(1)),
+        "week" => Ok(today.week(app.settings.week_start).first_day()),
+        "month" => Ok(today.with_day(1).expect("First day of month is valid")),
+        _ => NaiveDate::parse_from_str(day, "%Y-%m-%d")
+            .map_err(|_| anyhow!("Invalid date `{}`, expected YYYY-MM-DD", day)),
+    }
+}
+
+fn find_project(app: &App, name: &str) -> anyhow::Result<ProjectId> {
+    app.repository
+        .projects
+        .values()
+        .find(|project| project.name == name)
+        .map(|project| project.id)
+        .ok_or_else(|| anyhow!("No project named `{}`", name))
+}
+
+fn find_task(app: &App, project: &str, name: &str) -> anyhow::Result<TaskId> {
+    let project_id = find_project(app, project)?;
+    Project::from_id(&project_id, &app.repository)
+        .tasks
+        .iter()
+        .find(|id| Task::from_id(id, &app.repository).name == name)
+        .cloned()
+        .ok_or_else(|| anyhow!("No task named `{}` in project `{}`", name, project))
+}
+
+/// Creates the task and its project when missing, adding the operations to
+/// record to the given ones.
+fn find_or_create_task(
+    app: &App,
+    transaction: &Transaction,
+    operations: &mut Vec<Operation>,
+    project: &str,
+    name: &str,
+) -> anyhow::Result<TaskId> {
+    if let Ok(task_id) = find_task(app, project, name) {
+        return Ok(task_id);
+    }
+    let project_id = match find_project(app, project) {
+        Ok(project_id) => project_id,
+        Err(_) => {
+            let project = transaction.create_project(project.to_string())?;
+            let project_id = project.id;
+            operations.push(Operation::InsertProject(project));
+            project_id
+        }
+    };
+    let task = history::create_task(&app.repository, transaction, &project_id, name.to_string())?;
+    let task_id = task.id;
+    operations.push(Operation::InsertTask(task));
+    Ok(task_id)
+}
+
+/// Fails if the project or the task exists and is archived, as the TUI does
+/// not offer them to start timers on.
+fn check_not_archived(app: &App, project: &str, name: &str) -> anyhow::Result<()> {
+    if let Ok(project_id) = find_project(app, project) {
+        if Project::from_id(&project_id, &app.repository).archived {
+            bail!("Project `{}` is archived", project);
+        }
+    }
+    if let Ok(task_id) = find_task(app, project, name) {
+        if Task::from_id(&task_id, &app.repository).archived {
+            bail!("Task `{}/{}` is archived", project, name);
+        }
+    }
+    Ok(())
+}
+
+fn entry_path(app: &App, entry: &Entry) -> String {
+    let task = Task::from_id(&entry.task_id, &app.repository);
+    let project = Project::from_id(&task.project_id, &app.repository);
+    format!("{}/{}", project.name, task.name)
+}
+
+fn archived_marker(archived: bool) -> &'static str {
+    match archived {
+        true => "  (archived)",
+        false => "",
+    }
+}
+
+fn entry_output<'a>(app: &'a App, entry: &'a Entry, now: DateTime<Local>) -> EntryOutput<'a> {
+    let task = Task::from_id(&entry.task_id, &app.repository);
+    let project = Project::from_id(&task.project_id, &app.repository);
+    EntryOutput {
+        entry,
+        project: &project.name,
+        task: &task.name,
+        duration: entry.duration(now).num_seconds(),
+    }
+}
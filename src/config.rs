@@ -0,0 +1,202 @@
+use std::env;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use chrono::{Local, Weekday};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use tui::style::Color;
+
+use crate::app::Settings;
+use crate::input::bindings::Bindings;
+
+const APP_DIRECTORY: &str = "doing-tui";
+const CONFIG_FILE: &str = "config.toml";
+const DATABASE_FILE: &str = "doing.db";
+const DATABASE_ENV: &str = "DOING_TUI_DB";
+
+/// `$XDG_CONFIG_HOME/doing-tui`, defaulting to `~/.config/doing-tui`.
+pub fn config_dir() -> anyhow::Result<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_DATA_HOME/doing-tui`, defaulting to `~/.local/share/doing-tui`.
+pub fn data_dir() -> anyhow::Result<PathBuf> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+fn xdg_dir(variable: &str, fallback: &str) -> anyhow::Result<PathBuf> {
+    let base = match env::var_os(variable).filter(|value| !value.is_empty()) {
+        Some(base) => PathBuf::from(base),
+        None => home_dir()?.join(fallback),
+    };
+    Ok(base.join(APP_DIRECTORY))
+}
+
+fn home_dir() -> anyhow::Result<PathBuf> {
+    env::var_os("HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .context("Could not resolve the home directory, $HOME is not set")
+}
+
+fn expand_home(path: &Path) -> anyhow::Result<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(relative) => Ok(home_dir()?.join(relative)),
+        Err(_) => Ok(path.to_path_buf()),
+    }
+}
+
+const DEFAULT_CONFIG: &str = r##"# doing-tui configuration
+# Every option is optional, uncomment to override the default.
+
+# Path of the SQLite database, overridden by --db and DOING_TUI_DB.
+# Defaults to $XDG_DATA_HOME/doing-tui/doing.db
+#database = "~/.local/share/doing-tui/doing.db"
+
+# Formats of dates and times, see
+# https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+#date_format = "%-d %b %Y"
+#time_format = "%H:%M"
+
+# First day of the week in reports, e.g. "monday" or "sunday".
+#week_start = "monday"
+
+# Round displayed durations to the nearest multiple of this many minutes,
+# 0 disables rounding.
+#rounding = 0
+
+# Colors are names ("blue", "lightred", "gray", ...), "#rrggbb" or a 256 colors index.
+[theme]
+#accent = "blue"
+#running = "green"
+#error = "red"
+
+# Keys of each action, either a key or a list of keys. Modifiers are written
+# "ctrl+n", "alt+x" or "shift+tab", and sequences "gg" or "ctrl+x ctrl+s".
+# Setting an action replaces all its default keys, [] unbinds it.
+[keybindings]
+"##;
+
+/// Template printed by `dt config --print-default`, every option is set to its default.
+pub fn default_config() -> String {
+    format!("{}{}", DEFAULT_CONFIG, Bindings::default_config())
+}
+
+pub fn config_path() -> anyhow::Result<PathBuf> {
+    Ok(config_dir()?.join(CONFIG_FILE))
+}
+
+/// Loads the settings from the config file, using defaults if it does not exist.
+pub fn load_settings() -> anyhow::Result<Settings> {
+    let path = config_path()?;
+    if !path.exists() {
+        return Ok(Settings::default());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Could not read config file {}", path.display()))?;
+    let invalid = || format!("Invalid config file {}", path.display());
+    let settings: Settings = toml::from_str(&content).with_context(invalid)?;
+    // Dates are formatted from days, which have no time fields
+    let day = Local::now().date_naive();
+    check_format("date_format", &settings.date_format, |text| {
+        write!(text, "{}", day.format(&settings.date_format))
+    })
+    .with_context(invalid)?;
+    check_format("time_format", &settings.time_format, |text| {
+        write!(text, "{}", Local::now().format(&settings.time_format))
+    })
+    .with_context(invalid)?;
+    Ok(settings)
+}
+
+/// Rejects formats which chrono would fail to render when drawing, by
+/// rendering a sample value the way the app does.
+fn check_format<F>(key: &str, format: &str, render: F) -> anyhow::Result<()>
+where
+    F: FnOnce(&mut String) -> fmt::Result,
+{
+    if render(&mut String::new()).is_err() {
+        bail!("invalid format `{}` for `{}`", format, key);
+    }
+    Ok(())
+}
+
+/// Resolves the database location from, in order, the `--db` flag, the
+/// `DOING_TUI_DB` environment variable, the config file and the XDG data
+/// directory. Parent directories are created if needed.
+pub fn database_path(flag: Option<PathBuf>, settings: &Settings) -> anyhow::Result<PathBuf> {
+    let path = match flag
+        .or_else(|| {
+            env::var_os(DATABASE_ENV)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        })
+        .or_else(|| settings.database.clone())
+    {
+        Some(path) => expand_home(&path)?,
+        None => data_dir()?.join(DATABASE_FILE),
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create directory {}", parent.display()))?;
+    }
+    Ok(path)
+}
+
+pub fn deserialize_weekday<'de, D>(deserializer: D) -> Result<Weekday, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    value
+        .parse()
+        .map_err(|_| D::Error::custom(format!("invalid weekday `{}`", value)))
+}
+
+pub fn deserialize_color<'de, D>(deserializer: D) -> Result<Color, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse_color(&value).ok_or_else(|| D::Error::custom(format!("invalid color `{}`", value)))
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+    if let Ok(index) = value.parse() {
+        return Some(Color::Indexed(index));
+    }
+    let color = match value.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
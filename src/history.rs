@@ -0,0 +1,427 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::app::Repository;
+use crate::model::*;
+use crate::storage::Transaction;
+
+/// Number of changes kept in the database to be undone.
+pub const HISTORY_LIMIT: u32 = 100;
+
+/// A user action recorded so it can be undone, made of operations applied in order.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Change {
+    pub description: String,
+    pub operations: Vec<Operation>,
+}
+
+/// A single reversible mutation of both the storage and the repository.
+///
+/// Deleted models are kept whole so they can be restored with the same id.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Operation {
+    InsertTag(Tag),
+    DeleteTag(Tag),
+    RenameTag {
+        id: TagId,
+        from: String,
+        to: String,
+    },
+
+    /// Restores the project and its default tags, but not its tasks.
+    InsertProject(Project),
+    DeleteProject(Project),
+    RenameProject {
+        id: ProjectId,
+        from: String,
+        to: String,
+    },
+    SetProjectArchived {
+        id: ProjectId,
+        archived: bool,
+    },
+    AddDefaultTag {
+        project_id: ProjectId,
+        tag_id: TagId,
+    },
+    RemoveDefaultTag {
+        project_id: ProjectId,
+        tag_id: TagId,
+    },
+
+    /// Restores the task and its tags, but not its entries.
+    InsertTask(Task),
+    DeleteTask(Task),
+    RenameTask {
+        id: TaskId,
+        from: String,
+        to: String,
+    },
+    EditTaskNotes {
+        id: TaskId,
+        from: String,
+        to: String,
+    },
+    MoveTask {
+        id: TaskId,
+        from: ProjectId,
+        to: ProjectId,
+    },
+    SetTaskArchived {
+        id: TaskId,
+        archived: bool,
+    },
+    AddTaskTag {
+        task_id: TaskId,
+        tag_id: TagId,
+    },
+    RemoveTaskTag {
+        task_id: TaskId,
+        tag_id: TagId,
+    },
+
+    InsertEntry(Entry),
+    DeleteEntry(Entry),
+    UpdateEntry {
+        from: Entry,
+        to: Entry,
+    },
+}
+
+impl Change {
+    pub fn new<S: Into<String>>(description: S, operations: Vec<Operation>) -> Self {
+        Self {
+            description: description.into(),
+            operations,
+        }
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            description: self.description.clone(),
+            operations: self
+                .operations
+                .iter()
+                .rev()
+                .map(Operation::inverse)
+                .collect(),
+        }
+    }
+
+    /// Writes the operations to the storage in order, without committing them.
+    pub fn write(&self, transaction: &Transaction) -> anyhow::Result<()> {
+        self.operations
+            .iter()
+            .try_for_each(|operation| operation.write(transaction))
+    }
+
+    /// Applies the operations to the repository once they are committed.
+    pub fn apply(&self, repository: &mut Repository) {
+        for operation in &self.operations {
+            operation.apply(repository);
+        }
+    }
+
+    /// Records a change whose operations were written with the transaction,
+    /// then commits both and applies the change to the repository.
+    ///
+    /// Changes without operations are not recorded.
+    pub fn record(
+        &self,
+        transaction: Transaction,
+        repository: &mut Repository,
+    ) -> anyhow::Result<()> {
+        if !self.operations.is_empty() {
+            transaction.push_change(self)?;
+        }
+        transaction.commit()?;
+        self.apply(repository);
+        Ok(())
+    }
+}
+
+impl Operation {
+    pub fn inverse(&self) -> Self {
+        match self.clone() {
+            Self::InsertTag(tag) => Self::DeleteTag(tag),
+            Self::DeleteTag(tag) => Self::InsertTag(tag),
+            Self::RenameTag { id, from, to } => Self::RenameTag {
+                id,
+                from: to,
+                to: from,
+            },
+
+            Self::InsertProject(project) => Self::DeleteProject(project),
+            Self::DeleteProject(project) => Self::InsertProject(project),
+            Self::RenameProject { id, from, to } => Self::RenameProject {
+                id,
+                from: to,
+                to: from,
+            },
+            Self::SetProjectArchived { id, archived } => Self::SetProjectArchived {
+                id,
+                archived: !archived,
+            },
+            Self::AddDefaultTag { project_id, tag_id } => {
+                Self::RemoveDefaultTag { project_id, tag_id }
+            }
+            Self::RemoveDefaultTag { project_id, tag_id } => {
+                Self::AddDefaultTag { project_id, tag_id }
+            }
+
+            Self::InsertTask(task) => Self::DeleteTask(task),
+            Self::DeleteTask(task) => Self::InsertTask(task),
+            Self::RenameTask { id, from, to } => Self::RenameTask {
+                id,
+                from: to,
+                to: from,
+            },
+            Self::EditTaskNotes { id, from, to } => Self::EditTaskNotes {
+                id,
+                from: to,
+                to: from,
+            },
+            Self::MoveTask { id, from, to } => Self::MoveTask {
+                id,
+                from: to,
+                to: from,
+            },
+            Self::SetTaskArchived { id, archived } => Self::SetTaskArchived {
+                id,
+                archived: !archived,
+            },
+            Self::AddTaskTag { task_id, tag_id } => Self::RemoveTaskTag { task_id, tag_id },
+            Self::RemoveTaskTag { task_id, tag_id } => Self::AddTaskTag { task_id, tag_id },
+
+            Self::InsertEntry(entry) => Self::DeleteEntry(entry),
+            Self::DeleteEntry(entry) => Self::InsertEntry(entry),
+            Self::UpdateEntry { from, to } => Self::UpdateEntry { from: to, to: from },
+        }
+    }
+
+    /// Writes the operation to the storage, failing if the rows it touches no
+    /// longer hold the values it expects.
+    fn write(&self, transaction: &Transaction) -> anyhow::Result<()> {
+        match self {
+            Self::InsertTag(tag) => transaction.restore_tag(tag),
+            Self::DeleteTag(tag) => transaction.delete_tag(tag),
+            Self::RenameTag { id, from, to } => transaction.rename_tag(id, from, to),
+
+            Self::InsertProject(project) => transaction.restore_project(project),
+            Self::DeleteProject(project) => transaction.delete_project(project),
+            Self::RenameProject { id, from, to } => transaction.rename_project(id, from, to),
+            Self::SetProjectArchived { id, archived } => {
+                transaction.set_project_archived(id, *archived)
+            }
+            Self::AddDefaultTag { project_id, tag_id } => {
+                transaction.add_default_tag(project_id, tag_id)
+            }
+            Self::RemoveDefaultTag { project_id, tag_id } => {
+                transaction.remove_default_tag(project_id, tag_id)
+            }
+
+            Self::InsertTask(task) => transaction.restore_task(task),
+            Self::DeleteTask(task) => transaction.delete_task(task),
+            Self::RenameTask { id, from, to } => transaction.rename_task(id, from, to),
+            Self::EditTaskNotes { id, from, to } => transaction.set_task_notes(id, from, to),
+            Self::MoveTask { id, from, to } => transaction.move_task(id, from, to),
+            Self::SetTaskArchived { id, archived } => transaction.set_task_archived(id, *archived),
+            Self::AddTaskTag { task_id, tag_id } => transaction.add_task_tag(task_id, tag_id),
+            Self::RemoveTaskTag { task_id, tag_id } => transaction.remove_task_tag(task_id, tag_id),
+
+            Self::InsertEntry(entry) => transaction.restore_entry(entry),
+            Self::DeleteEntry(entry) => transaction.delete_entry(entry),
+            Self::UpdateEntry { from, to } => transaction.update_entry(from, to),
+        }
+    }
+
+    fn apply(&self, repository: &mut Repository) {
+        match self {
+            Self::InsertTag(tag) => repository.add_tag(tag.clone()),
+            Self::DeleteTag(tag) => repository.remove_tag(&tag.id),
+            Self::RenameTag { id, to, .. } => repository.rename_tag(id, to.clone()),
+
+            Self::InsertProject(project) => repository.add_project(Project {
+                tasks: Vec::new(),
+                ..project.clone()
+            }),
+            Self::DeleteProject(project) => repository.remove_project(&project.id),
+            Self::RenameProject { id, to, .. } => repository.rename_project(id, to.clone()),
+            Self::SetProjectArchived { id, archived } => {
+                repository.set_project_archived(id, *archived)
+            }
+            Self::AddDefaultTag { project_id, tag_id } => {
+                repository.add_default_tag(project_id, *tag_id)
+            }
+            Self::RemoveDefaultTag { project_id, tag_id } => {
+                repository.remove_default_tag(project_id, tag_id)
+            }
+
+            Self::InsertTask(task) => repository.add_task(task.clone()),
+            Self::DeleteTask(task) => repository.remove_task(&task.id),
+            Self::RenameTask { id, to, .. } => repository.rename_task(id, to.clone()),
+            Self::EditTaskNotes { id, to, .. } => repository.set_task_notes(id, to.clone()),
+            Self::MoveTask { id, to, .. } => repository.move_task(id, *to),
+            Self::SetTaskArchived { id, archived } => repository.set_task_archived(id, *archived),
+            Self::AddTaskTag { task_id, tag_id } => repository.add_task_tag(task_id, *tag_id),
+            Self::RemoveTaskTag { task_id, tag_id } => repository.remove_task_tag(task_id, tag_id),
+
+            Self::InsertEntry(entry) => repository.add_entry(entry.clone()),
+            Self::DeleteEntry(entry) => repository.remove_entry(&entry.id),
+            Self::UpdateEntry { to, .. } => repository.update_entry(to.clone()),
+        }
+    }
+}
+
+/// Unlinks a tag from tasks and projects before deleting it.
+pub fn delete_tag(repository: &Repository, tag_id: &TagId) -> Vec<Operation> {
+    let mut operations: Vec<Operation> = repository
+        .tasks
+        .values()
+        .filter(|task| task.tags.contains(tag_id))
+        .map(|task| Operation::RemoveTaskTag {
+            task_id: task.id,
+            tag_id: *tag_id,
+        })
+        .collect();
+    operations.extend(
+        repository
+            .projects
+            .values()
+            .filter(|project| project.default_tags.contains(tag_id))
+            .map(|project| Operation::RemoveDefaultTag {
+                project_id: project.id,
+                tag_id: *tag_id,
+            }),
+    );
+    operations.push(Operation::DeleteTag(
+        Tag::from_id(tag_id, repository).clone(),
+    ));
+    operations
+}
+
+/// Deletes the entries of a task before the task itself.
+pub fn delete_task(repository: &Repository, task_id: &TaskId) -> Vec<Operation> {
+    let mut operations: Vec<Operation> = repository
+        .task_entries(task_id)
+        .into_iter()
+        .map(|entry| Operation::DeleteEntry(entry.clone()))
+        .collect();
+    operations.push(Operation::DeleteTask(
+        Task::from_id(task_id, repository).clone(),
+    ));
+    operations
+}
+
+/// Deletes the tasks of a project before the project itself.
+pub fn delete_project(repository: &Repository, project_id: &ProjectId) -> Vec<Operation> {
+    let project = Project::from_id(project_id, repository);
+    let mut operations: Vec<Operation> = project
+        .tasks
+        .iter()
+        .flat_map(|task_id| delete_task(repository, task_id))
+        .collect();
+    operations.push(Operation::DeleteProject(Project {
+        tasks: Vec::new(),
+        ..project.clone()
+    }));
+    operations
+}
+
+/// Adds the default tags of a project to those of its tasks missing them.
+pub fn apply_default_tags(repository: &Repository, project_id: &ProjectId) -> Vec<Operation> {
+    let project = Project::from_id(project_id, repository);
+    let mut operations = Vec::new();
+    for task_id in &project.tasks {
+        let task = Task::from_id(task_id, repository);
+        for tag_id in &project.default_tags {
+            if !task.tags.contains(tag_id) {
+                operations.push(Operation::AddTaskTag {
+                    task_id: *task_id,
+                    tag_id: *tag_id,
+                });
+            }
+        }
+    }
+    operations
+}
+
+/// Replaces the tags a task got from the defaults of a project by the defaults
+/// of another, usually after moving it.
+pub fn swap_default_tags(
+    repository: &Repository,
+    task_id: &TaskId,
+    from: &ProjectId,
+    to: &ProjectId,
+) -> Vec<Operation> {
+    let (removed, added) = repository.default_tags_swap(task_id, from, to);
+    let removed = removed.into_iter().map(|tag_id| Operation::RemoveTaskTag {
+        task_id: *task_id,
+        tag_id,
+    });
+    let added = added.into_iter().map(|tag_id| Operation::AddTaskTag {
+        task_id: *task_id,
+        tag_id,
+    });
+    removed.chain(added).collect()
+}
+
+/// Creates a task with the default tags of its project.
+pub fn create_task(
+    repository: &Repository,
+    transaction: &Transaction,
+    project_id: &ProjectId,
+    name: String,
+) -> anyhow::Result<Task> {
+    let mut task = transaction.create_task(project_id, name)?;
+    // A project created in the same transaction has no default tags yet
+    if let Some(project) = repository.projects.get(project_id) {
+        for tag_id in &project.default_tags {
+            transaction.add_task_tag(&task.id, tag_id)?;
+            task.tags.push(*tag_id);
+        }
+    }
+    Ok(task)
+}
+
+/// Ends the running entry, if any.
+pub fn stop_timer(
+    repository: &Repository,
+    transaction: &Transaction,
+    now: DateTime<Local>,
+) -> anyhow::Result<Option<Operation>> {
+    match repository.running_entry() {
+        Some(running) => {
+            let operation = Operation::UpdateEntry {
+                from: running.clone(),
+                to: Entry {
+                    end: Some(now),
+                    ..running.clone()
+                },
+            };
+            operation.write(transaction)?;
+            Ok(Some(operation))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Starts a running entry on the given task, stopping any other running one.
+pub fn start_timer(
+    repository: &Repository,
+    transaction: &Transaction,
+    task_id: &TaskId,
+    now: DateTime<Local>,
+) -> anyhow::Result<Vec<Operation>> {
+    if let Some(running) = repository.running_entry() {
+        if running.task_id == *task_id {
+            return Ok(Vec::new());
+        }
+    }
+    let mut operations: Vec<Operation> = stop_timer(repository, transaction, now)?
+        .into_iter()
+        .collect();
+    let entry = transaction.create_entry(task_id, now, None)?;
+    operations.push(Operation::InsertEntry(entry));
+    Ok(operations)
+}
@@ -1,108 +1,512 @@
-use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use chrono::{DateTime, Local};
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+use unicode_width::UnicodeWidthStr;
 
 use crate::app::{App, Pane, Repository};
-use crate::model::TagId;
+use crate::history::{Change, Operation};
+use crate::model::{Entry, FromId, Project, ProjectId, Tag, TagId, Task, TaskId};
 use crate::prompts;
 use crate::ui::explorer::{Explorer, ExplorerGroup};
+use crate::ui::timeline::{self, Slot};
+use crate::ui::util;
+
+use self::bindings::{Action, Context, Key, Resolution};
+
+pub mod bindings;
+pub mod fuzzy;
+pub mod time;
 
 pub enum Prompt {
     Input(InputPrompt),
-    TagSelect(TagSelectPrompt),
+    DateTime(DateTimePrompt),
+    TagSelect(SelectPrompt<Tag>),
+    ProjectSelect(SelectPrompt<Project>),
+    TaskSelect(SelectPrompt<Task>),
     Confirm(ConfirmPrompt),
+    Help(HelpPrompt),
 }
 
 impl Prompt {
     pub fn suggest(self, suggest: String) -> Self {
         match self {
             Self::Input(input) => Self::Input(input.suggest(suggest)),
+            Self::DateTime(prompt) => Self::DateTime(DateTimePrompt {
+                input: prompt.input.suggest(suggest),
+            }),
             _ => unimplemented!("Suggest on prompt is only a shorthand for input prompts"),
         }
     }
 
+    pub fn contexts(&self) -> &'static [Context] {
+        match self {
+            Self::Input(_) | Self::DateTime(_) => &[Context::InputPrompt, Context::Prompt],
+            Self::TagSelect(_) => &[
+                Context::TagSelectPrompt,
+                Context::SelectPrompt,
+                Context::Prompt,
+            ],
+            Self::ProjectSelect(_) | Self::TaskSelect(_) => {
+                &[Context::SelectPrompt, Context::Prompt]
+            }
+            Self::Confirm(_) => &[Context::Prompt],
+            Self::Help(_) => &[Context::Help, Context::Prompt],
+        }
+    }
+
     pub fn awake(&mut self, repository: &Repository) {
         match self {
             Self::TagSelect(tag_select) => tag_select.update(repository),
+            Self::ProjectSelect(project_select) => project_select.update(repository),
+            Self::TaskSelect(task_select) => task_select.update(repository),
             _ => {}
         }
     }
 }
 
+// Callbacks can be called again after an error, once the prompt was reopened.
+type Callback = Box<dyn Fn(&mut App) -> anyhow::Result<()>>;
+type ValueCallback<T> = Box<dyn Fn(&mut App, T) -> anyhow::Result<()>>;
+
 pub struct InputPrompt {
     pub title: String,
-    pub callback: Box<dyn FnOnce(&mut App, String) -> anyhow::Result<()>>,
+    pub callback: ValueCallback<String>,
 
+    /// Maximum number of characters.
     pub limit: usize,
     pub alphanumeric: bool,
+    /// Whether an empty value can be confirmed, such as to clear a field.
+    pub allow_empty: bool,
     pub value: String,
+    /// Position of the cursor, in characters.
+    pub cursor: usize,
 }
 
 impl InputPrompt {
     pub fn new<S, C>(title: S, limit: usize, only_ascii: bool, callback: C) -> Self
     where
         S: Into<String>,
-        C: FnOnce(&mut App, String) -> anyhow::Result<()> + 'static,
+        C: Fn(&mut App, String) -> anyhow::Result<()> + 'static,
     {
         Self {
             title: title.into(),
             callback: Box::new(callback),
             limit,
             alphanumeric: only_ascii,
+            allow_empty: false,
             value: String::new(),
+            cursor: 0,
         }
     }
 
     pub fn suggest(mut self, suggest: String) -> Self {
         self.value = suggest;
+        self.cursor = self.len();
         self
     }
+
+    pub fn allow_empty(mut self) -> Self {
+        self.allow_empty = true;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    /// Display width of the value before the cursor.
+    pub fn cursor_width(&self) -> usize {
+        self.value[..self.byte_index(self.cursor)].width()
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.value.len(), |(index, _)| index)
+    }
+
+    /// Inserts text at the cursor, dropping the characters which are not
+    /// accepted or would go over the limit.
+    pub fn insert(&mut self, text: &str) {
+        let alphanumeric = self.alphanumeric;
+        let accepted = text
+            .chars()
+            .filter(|ch| !ch.is_control() && (!alphanumeric || ch.is_alphanumeric()));
+        for ch in accepted {
+            if self.len() >= self.limit {
+                break;
+            }
+            let index = self.byte_index(self.cursor);
+            self.value.insert(index, ch);
+            self.cursor += 1;
+        }
+    }
+
+    fn delete(&mut self, start: usize, end: usize) {
+        let range = self.byte_index(start)..self.byte_index(end);
+        self.value.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    /// Start of the word before the cursor, whitespace being the only separator.
+    fn previous_word(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().take(self.cursor).collect();
+        let mut index = chars.len();
+        while index > 0 && chars[index - 1].is_whitespace() {
+            index -= 1;
+        }
+        while index > 0 && !chars[index - 1].is_whitespace() {
+            index -= 1;
+        }
+        index
+    }
+
+    /// End of the word after the cursor.
+    fn next_word(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().collect();
+        let mut index = self.cursor;
+        while index < chars.len() && chars[index].is_whitespace() {
+            index += 1;
+        }
+        while index < chars.len() && !chars[index].is_whitespace() {
+            index += 1;
+        }
+        index
+    }
+
+    /// Handles the cursor movements and edits of the value.
+    fn edit(&mut self, action: Option<Action>, key: &Key) {
+        match action {
+            Some(Action::InputDelete) if self.cursor > 0 => {
+                self.delete(self.cursor - 1, self.cursor);
+            }
+            Some(Action::InputDeleteNext) if self.cursor < self.len() => {
+                self.delete(self.cursor, self.cursor + 1);
+            }
+            Some(Action::InputDeleteWord) => {
+                self.delete(self.previous_word(), self.cursor);
+            }
+            Some(Action::InputLeft) => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            Some(Action::InputRight) => {
+                self.cursor = (self.cursor + 1).min(self.len());
+            }
+            Some(Action::InputWordLeft) => {
+                self.cursor = self.previous_word();
+            }
+            Some(Action::InputWordRight) => {
+                self.cursor = self.next_word();
+            }
+            Some(Action::InputHome) => {
+                self.cursor = 0;
+            }
+            Some(Action::InputEnd) => {
+                self.cursor = self.len();
+            }
+            None => {
+                if let KeyCode::Char(ch) = key.code {
+                    if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+                        self.insert(ch.encode_utf8(&mut [0; 4]));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Input prompt for a point in time, parsed as it is typed to show which
+/// time it stands for.
+pub struct DateTimePrompt {
+    pub input: InputPrompt,
+}
+
+impl DateTimePrompt {
+    pub fn new<S, C>(title: S, callback: C) -> Self
+    where
+        S: Into<String>,
+        C: Fn(&mut App, DateTime<Local>) -> anyhow::Result<()> + 'static,
+    {
+        let input = InputPrompt::new(title, 40, false, move |app, value| {
+            let time = time::parse(&value, Local::now())?;
+            callback(app, time)
+        });
+        Self { input }
+    }
+
+    /// The typed time, or why it is not valid.
+    pub fn parse(&self) -> anyhow::Result<DateTime<Local>> {
+        time::parse(&self.input.value, Local::now())
+    }
+}
+
+/// Models which can be picked in a [`SelectPrompt`].
+pub trait Selectable: Sized + 'static {
+    type Id: for<'a> From<&'a Self> + Clone + Eq + Hash;
+
+    fn all(repository: &Repository) -> Vec<&Self>;
+    /// Text searched and displayed in the list.
+    fn label(&self, repository: &Repository) -> String;
+    /// Last time the item was worked on, recent items are listed first.
+    fn last_used(&self, repository: &Repository) -> Option<DateTime<Local>>;
+}
+
+impl Selectable for Tag {
+    type Id = TagId;
+
+    fn all(repository: &Repository) -> Vec<&Self> {
+        repository.tags.values().collect()
+    }
+
+    fn label(&self, _: &Repository) -> String {
+        self.name.clone()
+    }
+
+    fn last_used(&self, repository: &Repository) -> Option<DateTime<Local>> {
+        repository
+            .tasks
+            .values()
+            .filter(|task| task.tags.contains(&self.id))
+            .filter_map(|task| repository.task_last_used(&task.id))
+            .max()
+    }
+}
+
+impl Selectable for Project {
+    type Id = ProjectId;
+
+    fn all(repository: &Repository) -> Vec<&Self> {
+        repository.projects.values().collect()
+    }
+
+    fn label(&self, _: &Repository) -> String {
+        self.name.clone()
+    }
+
+    fn last_used(&self, repository: &Repository) -> Option<DateTime<Local>> {
+        self.tasks
+            .iter()
+            .filter_map(|task_id| repository.task_last_used(task_id))
+            .max()
+    }
 }
 
-pub struct TagSelectPrompt {
+impl Selectable for Task {
+    type Id = TaskId;
+
+    fn all(repository: &Repository) -> Vec<&Self> {
+        repository.tasks.values().collect()
+    }
+
+    /// Includes the project, tags and notes so searches can match them.
+    fn label(&self, repository: &Repository) -> String {
+        let project = Project::from_id(&self.project_id, repository);
+        let mut label = format!("{} > {}", project.name, self.name);
+        if !self.tags.is_empty() {
+            let tags = util::tag_names(repository, &self.tags);
+            label.push_str(&format!(" [{}]", tags.join(", ")));
+        }
+        if !self.notes.is_empty() {
+            label.push_str(&format!(" - {}", self.notes));
+        }
+        label
+    }
+
+    fn last_used(&self, repository: &Repository) -> Option<DateTime<Local>> {
+        repository.task_last_used(&self.id)
+    }
+}
+
+type SelectFilter<M> = Box<dyn Fn(&Repository, &M) -> bool>;
+
+pub struct SelectPrompt<M: Selectable> {
     pub title: String,
-    pub callback: Box<dyn FnOnce(&mut App, TagId) -> anyhow::Result<()>>,
+    pub callback: ValueCallback<Vec<M::Id>>,
+    filter: Option<SelectFilter<M>>,
+    /// Whether several items can be marked and confirmed at once.
+    pub multiple: bool,
 
     pub search: String,
-    pub explorer: ExplorerGroup<TagId>,
+    pub explorer: ExplorerGroup<M::Id>,
+    pub marked: Vec<M::Id>,
+    /// Indices of the characters of each label matching the search.
+    pub matches: HashMap<M::Id, Vec<usize>>,
 }
 
-impl TagSelectPrompt {
+impl<M: Selectable> SelectPrompt<M> {
     pub fn new<S, C>(title: S, callback: C) -> Self
     where
         S: Into<String>,
-        C: FnOnce(&mut App, TagId) -> anyhow::Result<()> + 'static,
+        C: Fn(&mut App, M::Id) -> anyhow::Result<()> + 'static,
+    {
+        Self::new_multiple(title, move |app, ids| match ids.into_iter().next() {
+            Some(id) => callback(app, id),
+            None => Ok(()),
+        })
+        .single()
+    }
+
+    /// A prompt where several items can be marked, the callback receives the
+    /// marked items or the selected one if none are.
+    pub fn new_multiple<S, C>(title: S, callback: C) -> Self
+    where
+        S: Into<String>,
+        C: Fn(&mut App, Vec<M::Id>) -> anyhow::Result<()> + 'static,
     {
         Self {
             title: title.into(),
             callback: Box::new(callback),
+            filter: None,
+            multiple: true,
             search: String::new(),
             explorer: ExplorerGroup::default(),
+            marked: Vec::new(),
+            matches: HashMap::new(),
+        }
+    }
+
+    fn single(mut self) -> Self {
+        self.multiple = false;
+        self
+    }
+
+    /// Only lists items matching the given predicate, evaluated on every update.
+    pub fn filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&Repository, &M) -> bool + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Items to call the callback with, none if the list is empty.
+    pub fn chosen(&self) -> Vec<M::Id> {
+        if self.marked.is_empty() {
+            self.explorer.selected_raw().cloned().into_iter().collect()
+        } else {
+            self.marked.clone()
         }
     }
 
     fn update(&mut self, repository: &Repository) {
-        let prefix = &self.search;
-        let no_filter = prefix.is_empty();
-        let items = repository
-            .tags
-            .values()
-            .filter(|tag| {
-                no_filter || (tag.name.len() >= prefix.len() && tag.name.starts_with(prefix))
+        let items: Vec<&M> = M::all(repository)
+            .into_iter()
+            .filter(|item| match &self.filter {
+                Some(filter) => filter(repository, item),
+                None => true,
             })
             .collect();
-        self.explorer.sync_and_sort(items, |item| item.name.clone());
+        self.marked
+            .retain(|id| items.iter().any(|item| M::Id::from(item) == *id));
+
+        let mut scores = HashMap::new();
+        self.matches.clear();
+        let items = items
+            .into_iter()
+            .filter(|item| {
+                let label = item.label(repository);
+                match fuzzy::fuzzy_match(&self.search, &label) {
+                    Some(found) => {
+                        let id = M::Id::from(item);
+                        scores.insert(id.clone(), (found.score, item.last_used(repository), label));
+                        self.matches.insert(id, found.indices);
+                        true
+                    }
+                    None => false,
+                }
+            })
+            .collect();
+        self.explorer.sync_and_sort(items, |item| {
+            let (score, last_used, label) = scores[&M::Id::from(item)].clone();
+            (Reverse(score), Reverse(last_used), label)
+        });
+    }
+
+    /// Handles the navigation, search and marking shared by every select prompt.
+    fn navigate(&mut self, action: Option<Action>, key: &Key, repository: &Repository) {
+        match action {
+            Some(Action::SelectPrevious) => {
+                self.explorer.previous();
+            }
+            Some(Action::SelectNext) => {
+                self.explorer.next();
+            }
+            Some(Action::SelectDelete) => {
+                self.search.pop();
+                self.update(repository);
+            }
+            Some(Action::SelectMark) if self.multiple => {
+                if let Some(id) = self.explorer.selected_raw().cloned() {
+                    match self.marked.iter().position(|marked| *marked == id) {
+                        Some(index) => {
+                            self.marked.remove(index);
+                        }
+                        None => self.marked.push(id),
+                    }
+                }
+            }
+            None => {
+                if let KeyCode::Char(ch) = key.code {
+                    if key.modifiers.difference(KeyModifiers::SHIFT).is_empty()
+                        && !ch.is_whitespace()
+                    {
+                        self.search.push(ch);
+                        self.update(repository);
+                    }
+                }
+            }
+            _ => {}
+        }
     }
 }
 
+macro_rules! impl_select_prompt {
+    ($($model:ty => $variant:ident),*) => {
+        $( impl From<SelectPrompt<$model>> for Prompt {
+            fn from(select: SelectPrompt<$model>) -> Self {
+                Prompt::$variant(select)
+            }
+        })*
+    };
+}
+
+impl_select_prompt!(Tag => TagSelect, Project => ProjectSelect, Task => TaskSelect);
+
+/// Calls the callback of a closed select prompt with the chosen items,
+/// reopening it if there are none or if the callback fails.
+fn confirm_select<M>(app: &mut App, select: SelectPrompt<M>) -> anyhow::Result<()>
+where
+    M: Selectable,
+    SelectPrompt<M>: Into<Prompt>,
+{
+    let chosen = select.chosen();
+    if chosen.is_empty() {
+        app.show_prompt(select.into());
+        return Ok(());
+    }
+    if let Err(error) = (select.callback)(app, chosen) {
+        app.show_prompt(select.into());
+        return Err(error);
+    }
+    app.awake_prompt();
+    Ok(())
+}
+
 pub struct ConfirmPrompt {
     pub action: String,
-    pub callback: Box<dyn FnOnce(&mut App) -> anyhow::Result<()>>,
+    pub callback: Callback,
 }
 
 impl ConfirmPrompt {
     pub fn new<S, C>(action: S, callback: C) -> Self
     where
         S: Into<String>,
-        C: FnOnce(&mut App) -> anyhow::Result<()> + 'static,
+        C: Fn(&mut App) -> anyhow::Result<()> + 'static,
     {
         Self {
             action: action.into(),
@@ -111,161 +515,343 @@ impl ConfirmPrompt {
     }
 }
 
+pub struct HelpPrompt {
+    /// Contexts of the focused pane or prompt when the help was opened.
+    pub contexts: Vec<Context>,
+    pub scroll: u16,
+}
+
+impl HelpPrompt {
+    pub fn new(contexts: &[Context]) -> Self {
+        Self {
+            contexts: contexts.to_vec(),
+            scroll: 0,
+        }
+    }
+}
+
 pub fn handle_event(app: &mut App, event: Event) -> anyhow::Result<bool> {
+    if let Event::Paste(text) = &event {
+        match app.state.prompt_stack.last_mut() {
+            Some(Prompt::Input(input)) => input.insert(text),
+            Some(Prompt::DateTime(prompt)) => prompt.input.insert(text),
+            _ => {}
+        }
+        return Ok(false);
+    }
     if let Event::Key(key) = event {
+        let key = Key::from(key);
         if let Some(prompt) = app.state.prompt_stack.last_mut() {
+            let contexts = prompt.contexts();
+            let action = match app
+                .bindings
+                .resolve(contexts, &mut app.state.pending_keys, key)
+            {
+                Resolution::Action(action) => Some(action),
+                Resolution::Pending => return Ok(false),
+                Resolution::Unbound => None,
+            };
+            if action == Some(Action::PromptHelp) {
+                app.show_prompt(Prompt::Help(HelpPrompt::new(contexts)));
+                return Ok(false);
+            }
             match prompt {
-                Prompt::Input(input) => match key.code {
-                    KeyCode::Esc => {
+                Prompt::Input(input) => match action {
+                    Some(Action::PromptCancel) => {
                         app.close_prompt();
                     }
-                    KeyCode::Enter => {
-                        if !input.value.is_empty() {
-                            if let Some(Prompt::Input(input)) = app.close_prompt() {
-                                let callback = input.callback;
-                                callback(app, input.value)?;
-                                app.awake_prompt();
+                    Some(Action::PromptConfirm) if input.allow_empty || !input.value.is_empty() => {
+                        if let Some(Prompt::Input(input)) = app.close_prompt() {
+                            if let Err(error) = (input.callback)(app, input.value.clone()) {
+                                app.show_prompt(Prompt::Input(input));
+                                return Err(error);
                             }
+                            app.awake_prompt();
                         }
                     }
-
-                    KeyCode::Char(ch) => {
-                        if key.modifiers.difference(KeyModifiers::SHIFT).is_empty()
-                            && (!input.alphanumeric || ch.is_alphanumeric())
-                        {
-                            if input.value.len() < input.limit {
-                                input.value.push(ch);
+                    action => input.edit(action, &key),
+                },
+                Prompt::DateTime(prompt) => match action {
+                    Some(Action::PromptCancel) => {
+                        app.close_prompt();
+                    }
+                    Some(Action::PromptConfirm) => {
+                        if let Some(Prompt::DateTime(prompt)) = app.close_prompt() {
+                            let value = prompt.input.value.clone();
+                            if let Err(error) = (prompt.input.callback)(app, value) {
+                                app.show_prompt(Prompt::DateTime(prompt));
+                                return Err(error);
                             }
+                            app.awake_prompt();
                         }
                     }
-                    KeyCode::Backspace => {
-                        input.value.pop();
-                    }
-                    _ => {}
+                    action => prompt.input.edit(action, &key),
                 },
-                Prompt::TagSelect(tag_select) => match key.code {
-                    KeyCode::Esc => {
+                Prompt::TagSelect(tag_select) => match action {
+                    Some(Action::PromptCancel) => {
                         app.close_prompt();
                     }
-                    KeyCode::Enter => {
-                        if let Some(selected) = tag_select.explorer.selected_raw().cloned() {
-                            if let Some(Prompt::TagSelect(tag_select)) = app.close_prompt() {
-                                let callback = tag_select.callback;
-                                callback(app, selected)?;
-                                app.awake_prompt();
-                            }
+                    Some(Action::PromptConfirm) => {
+                        if let Some(Prompt::TagSelect(tag_select)) = app.close_prompt() {
+                            confirm_select(app, tag_select)?;
                         }
                     }
 
-                    KeyCode::Up => {
-                        tag_select.explorer.previous();
+                    Some(Action::SelectNewTag) => {
+                        let prompt = prompts::new_tag(&tag_select.search);
+                        app.show_prompt(prompt);
                     }
-                    KeyCode::Down => {
-                        tag_select.explorer.next();
+                    Some(Action::SelectRenameTag) => {
+                        if let Some(tag) = tag_select.explorer.selected::<Tag>(&app.repository) {
+                            let prompt = prompts::rename_tag(tag.id).suggest(tag.name.clone());
+                            app.show_prompt(prompt);
+                        }
                     }
-
-                    KeyCode::Char(ch) => {
-                        if key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
-                            if ch.is_alphanumeric() {
-                                tag_select.search.push(ch);
-                                tag_select.update(&app.repository);
-                            }
-                        } else if key.modifiers.contains(KeyModifiers::CONTROL) {
-                            match ch {
-                                'k' => {
-                                    tag_select.explorer.previous();
-                                }
-                                'j' => {
-                                    tag_select.explorer.next();
-                                }
-
-                                'n' => {
-                                    let suggest = tag_select.search.clone();
-                                    app.show_prompt(prompts::new_tag().suggest(suggest));
-                                }
-                                'd' => {
-                                    if let Some(tag_id) =
-                                        tag_select.explorer.selected_raw().cloned()
-                                    {
-                                        app.show_prompt(prompts::delete_tag(tag_id));
-                                    }
-                                }
-                                _ => {}
-                            }
+                    Some(Action::SelectDeleteTag) => {
+                        if let Some(tag_id) = tag_select.explorer.selected_raw().cloned() {
+                            let prompt = prompts::delete_tag(app, tag_id);
+                            app.show_prompt(prompt);
                         }
                     }
-                    KeyCode::Backspace => {
-                        tag_select.search.pop();
-                        tag_select.update(&app.repository);
+                    action => tag_select.navigate(action, &key, &app.repository),
+                },
+                Prompt::ProjectSelect(project_select) => match action {
+                    Some(Action::PromptCancel) => {
+                        app.close_prompt();
                     }
-                    _ => {}
+                    Some(Action::PromptConfirm) => {
+                        if let Some(Prompt::ProjectSelect(project_select)) = app.close_prompt() {
+                            confirm_select(app, project_select)?;
+                        }
+                    }
+                    action => project_select.navigate(action, &key, &app.repository),
                 },
-                Prompt::Confirm(_) => match key.code {
-                    KeyCode::Esc => {
+                Prompt::TaskSelect(task_select) => match action {
+                    Some(Action::PromptCancel) => {
                         app.close_prompt();
                     }
-                    KeyCode::Enter => {
+                    Some(Action::PromptConfirm) => {
+                        if let Some(Prompt::TaskSelect(task_select)) = app.close_prompt() {
+                            confirm_select(app, task_select)?;
+                        }
+                    }
+                    action => task_select.navigate(action, &key, &app.repository),
+                },
+                Prompt::Confirm(_) => match action {
+                    Some(Action::PromptCancel) => {
+                        app.close_prompt();
+                    }
+                    Some(Action::PromptConfirm) => {
                         if let Some(Prompt::Confirm(confirm)) = app.close_prompt() {
-                            let callback = confirm.callback;
-                            callback(app)?;
+                            if let Err(error) = (confirm.callback)(app) {
+                                app.show_prompt(Prompt::Confirm(confirm));
+                                return Err(error);
+                            }
                             app.awake_prompt();
                         }
                     }
                     _ => {}
                 },
+                Prompt::Help(help) => match action {
+                    Some(Action::PromptCancel | Action::PromptConfirm) => {
+                        app.close_prompt();
+                    }
+                    Some(Action::HelpUp) => {
+                        help.scroll = help.scroll.saturating_sub(1);
+                    }
+                    Some(Action::HelpDown) => {
+                        help.scroll = help.scroll.saturating_add(1);
+                    }
+                    _ => {}
+                },
             }
         } else {
-            match key.code {
-                KeyCode::Esc => {
-                    if matches!(app.state.focus, Pane::Main) {
+            let contexts = Context::of_pane(&app.state.focus);
+            let action = match app
+                .bindings
+                .resolve(&contexts, &mut app.state.pending_keys, key)
+            {
+                Resolution::Action(action) => Some(action),
+                Resolution::Pending => return Ok(false),
+                Resolution::Unbound => None,
+            };
+            match action {
+                Some(Action::Back) => match app.state.focus {
+                    Pane::ProjectExplorer => return Ok(true),
+                    Pane::Main => {
                         app.state.explorer.collapsed = false;
                         app.update_focus();
-                    } else {
-                        return Ok(true);
                     }
-                }
-                KeyCode::Enter => {
-                    if matches!(app.state.focus, Pane::ProjectExplorer) {
+                    Pane::Entries => {
+                        app.state.focus = Pane::Main;
+                    }
+                    Pane::Reports | Pane::Stats | Pane::Timeline => {
+                        app.update_focus();
+                    }
+                    Pane::Calendar if app.state.calendar.open => {
+                        app.state.calendar.open = false;
+                    }
+                    Pane::Calendar => {
+                        app.update_focus();
+                    }
+                },
+                Some(Action::Open) => match app.state.focus {
+                    Pane::ProjectExplorer => {
                         app.state.explorer.collapsed = true;
                         app.update_focus();
                     }
-                }
+                    Pane::Calendar => {
+                        app.state.calendar.open = true;
+                    }
+                    Pane::Timeline => {
+                        let day = app.state.timeline.day();
+                        let slots = timeline::slots(&app.repository, day, Local::now());
+                        if let Some(Slot::Gap { start, end }) =
+                            slots.get(app.state.timeline.selected)
+                        {
+                            let prompt = prompts::fill_gap(*start, *end);
+                            app.show_prompt(prompt);
+                            app.awake_prompt();
+                        }
+                    }
+                    _ => {}
+                },
 
-                KeyCode::Char('q') => return Ok(true),
-                KeyCode::Char('?') => {}
-                KeyCode::Char(c @ '<') | KeyCode::Char(c @ '>') => {
-                    app.state.explorer.collapsed = c == '<';
+                Some(Action::Quit) => return Ok(true),
+                Some(Action::Help) => {
+                    app.show_prompt(Prompt::Help(HelpPrompt::new(&contexts)));
+                }
+                Some(Action::Undo) => match app.undo()? {
+                    Some(description) => app.info(format!("Undid {}", description)),
+                    None => app.info("Nothing to undo"),
+                },
+                Some(Action::Redo) => match app.redo()? {
+                    Some(description) => app.info(format!("Redid {}", description)),
+                    None => app.info("Nothing to redo"),
+                },
+                Some(action @ (Action::CollapseExplorer | Action::ExpandExplorer)) => {
+                    app.state.explorer.collapsed = action == Action::CollapseExplorer;
                     app.update_focus();
                 }
-
-                _ => match app.state.focus {
-                    Pane::ProjectExplorer => handle_project_explorer_key(key, app)?,
-                    Pane::Main => {
-                        handle_main_key(key, app)?;
+                Some(Action::Search) => {
+                    app.show_prompt(prompts::search());
+                    app.awake_prompt();
+                }
+                Some(Action::ReportsOpen) => {
+                    app.state.focus = Pane::Reports;
+                    // The current period may have changed since the last time
+                    app.state.reports.invalidate();
+                    app.state.reports.refresh(&app.repository, &app.settings);
+                }
+                Some(Action::StatsOpen) => {
+                    app.state.focus = Pane::Stats;
+                }
+                Some(Action::CalendarOpen) => {
+                    app.state.focus = Pane::Calendar;
+                }
+                Some(Action::TimelineOpen) => {
+                    if matches!(app.state.focus, Pane::Calendar) {
+                        let day = app.state.calendar.selected();
+                        app.state.timeline.show(day);
                     }
+                    app.state.focus = Pane::Timeline;
+                }
+                Some(Action::TimerPick) => {
+                    app.show_prompt(prompts::start_timer());
+                    app.awake_prompt();
+                }
+                Some(Action::ToggleArchived) => {
+                    app.state.explorer.show_archived = !app.state.explorer.show_archived;
+                    app.sync();
+                }
+
+                Some(action) => match app.state.focus {
+                    Pane::ProjectExplorer => handle_project_explorer_action(action, app)?,
+                    Pane::Main => handle_main_action(action, app)?,
+                    Pane::Entries => handle_entries_action(action, app)?,
+                    Pane::Reports => handle_reports_action(action, app),
+                    Pane::Stats => handle_stats_action(action, app),
+                    Pane::Calendar => handle_calendar_action(action, app),
+                    Pane::Timeline => handle_timeline_action(action, app),
                 },
+                None => {}
             }
         }
     }
     Ok(false)
 }
 
-fn handle_project_explorer_key(key: KeyEvent, app: &mut App) -> anyhow::Result<()> {
-    match key.code {
-        KeyCode::Up | KeyCode::Char('k') => {
+fn handle_project_explorer_action(action: Action, app: &mut App) -> anyhow::Result<()> {
+    match action {
+        Action::ExplorerPrevious => {
             app.state.explorer.projects.previous();
             app.state.explorer.project_changed(&app.repository);
         }
-        KeyCode::Down | KeyCode::Char('j') => {
+        Action::ExplorerNext => {
             app.state.explorer.projects.next();
             app.state.explorer.project_changed(&app.repository);
         }
-        KeyCode::Char('N') => {
+        Action::ExplorerFirst => {
+            app.state.explorer.projects.first();
+            app.state.explorer.project_changed(&app.repository);
+        }
+        Action::ExplorerLast => {
+            app.state.explorer.projects.last();
+            app.state.explorer.project_changed(&app.repository);
+        }
+        Action::ProjectNew => {
             app.show_prompt(prompts::new_project());
         }
-        KeyCode::Char('D') => {
+        Action::ProjectRename => {
+            if let Some(project) = app
+                .state
+                .explorer
+                .projects
+                .selected::<Project>(&app.repository)
+            {
+                let prompt = prompts::rename_project(project.id).suggest(project.name.clone());
+                app.show_prompt(prompt);
+            }
+        }
+        Action::ProjectArchive => {
+            if let Some(project) = app
+                .state
+                .explorer
+                .projects
+                .selected::<Project>(&app.repository)
+            {
+                let operation = Operation::SetProjectArchived {
+                    id: project.id,
+                    archived: !project.archived,
+                };
+                let description = match project.archived {
+                    true => "unarchive project",
+                    false => "archive project",
+                };
+                app.execute(Change::new(description, vec![operation]))?;
+            }
+        }
+        Action::ProjectDelete => {
             if let Some(project_id) = app.state.explorer.projects.selected_raw().cloned() {
-                app.show_prompt(prompts::delete_project(project_id));
+                let prompt = prompts::delete_project(app, project_id);
+                app.show_prompt(prompt);
+            }
+        }
+        Action::ProjectAddDefaultTag => {
+            if let Some(project_id) = app.state.explorer.projects.selected_raw().cloned() {
+                app.show_prompt(prompts::add_default_tag(project_id));
+                app.awake_prompt();
+            }
+        }
+        Action::ProjectRemoveDefaultTag => {
+            if let Some(project_id) = app.state.explorer.projects.selected_raw().cloned() {
+                app.show_prompt(prompts::remove_default_tag(project_id));
+                app.awake_prompt();
+            }
+        }
+        Action::ProjectApplyDefaultTags => {
+            if let Some(project_id) = app.state.explorer.projects.selected_raw().cloned() {
+                app.show_prompt(prompts::apply_default_tags(project_id));
             }
         }
         _ => {}
@@ -273,7 +859,7 @@ fn handle_project_explorer_key(key: KeyEvent, app: &mut App) -> anyhow::Result<(
     Ok(())
 }
 
-fn handle_main_key(key: KeyEvent, app: &mut App) -> anyhow::Result<()> {
+fn handle_main_action(action: Action, app: &mut App) -> anyhow::Result<()> {
     if let Some(project_id) = app.state.explorer.projects.selected_raw() {
         let tasks = app
             .state
@@ -281,32 +867,103 @@ fn handle_main_key(key: KeyEvent, app: &mut App) -> anyhow::Result<()> {
             .tasks
             .as_mut()
             .expect("Explorer tasks not in sync");
-        match key.code {
-            KeyCode::Up | KeyCode::Char('k') => {
+        match action {
+            Action::TaskPrevious => {
                 tasks.previous();
+                app.state.explorer.task_changed(&app.repository);
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Action::TaskNext => {
                 tasks.next();
+                app.state.explorer.task_changed(&app.repository);
+            }
+            Action::TaskFirst => {
+                tasks.first();
+                app.state.explorer.task_changed(&app.repository);
+            }
+            Action::TaskLast => {
+                tasks.last();
+                app.state.explorer.task_changed(&app.repository);
+            }
+            Action::FocusEntries if tasks.selected_raw().is_some() => {
+                app.state.focus = Pane::Entries;
             }
 
-            KeyCode::Char('N') => {
+            Action::TaskNew => {
                 let project_id = *project_id;
                 app.show_prompt(prompts::new_task(project_id));
             }
-            KeyCode::Char('D') => {
+            Action::TaskRename => {
+                if let Some(task) = tasks.selected::<Task>(&app.repository) {
+                    let prompt = prompts::rename_task(task.id).suggest(task.name.clone());
+                    app.show_prompt(prompt);
+                }
+            }
+            Action::TaskEditNotes => {
+                if let Some(task) = tasks.selected::<Task>(&app.repository) {
+                    let prompt = prompts::edit_task_notes(task.id).suggest(task.notes.clone());
+                    app.show_prompt(prompt);
+                }
+            }
+            Action::TaskMove => {
+                if let Some(task_id) = tasks.selected_raw().cloned() {
+                    app.show_prompt(prompts::move_task(task_id));
+                    app.awake_prompt();
+                }
+            }
+            Action::TaskArchive => {
+                if let Some(task) = tasks.selected::<Task>(&app.repository) {
+                    let operation = Operation::SetTaskArchived {
+                        id: task.id,
+                        archived: !task.archived,
+                    };
+                    let description = match task.archived {
+                        true => "unarchive task",
+                        false => "archive task",
+                    };
+                    app.execute(Change::new(description, vec![operation]))?;
+                }
+            }
+            Action::TaskDelete => {
                 if let Some(id) = tasks.selected_raw().cloned() {
-                    app.show_prompt(prompts::delete_task(id));
+                    let prompt = prompts::delete_task(app, id);
+                    app.show_prompt(prompt);
+                }
+            }
+
+            Action::TimerStart => {
+                if let Some(task_id) = tasks.selected_raw().cloned() {
+                    app.start_timer(task_id)?;
+                }
+            }
+            Action::TimerStop => {
+                app.stop_timer(Local::now())?;
+            }
+            Action::TimerToggle => {
+                if let Some(task_id) = tasks.selected_raw().cloned() {
+                    let running = app.repository.running_entry().map(|entry| entry.task_id);
+                    if running == Some(task_id) {
+                        app.stop_timer(Local::now())?;
+                    } else {
+                        app.start_timer(task_id)?;
+                    }
                 }
             }
 
-            KeyCode::Char('t') => {
+            Action::TaskAddEntry => {
+                if let Some(task_id) = tasks.selected_raw().cloned() {
+                    app.show_prompt(prompts::add_entry(task_id));
+                }
+            }
+            Action::TaskAddTag => {
+                if let Some(task_id) = tasks.selected_raw().cloned() {
+                    app.show_prompt(prompts::add_task_tag(task_id));
+                    app.awake_prompt();
+                }
+            }
+            Action::TaskRemoveTag => {
                 if let Some(task_id) = tasks.selected_raw().cloned() {
-                    let mut prompt = TagSelectPrompt::new("Add tag to task", move |app, tag_id| {
-                        // todo
-                        Ok(())
-                    });
-                    prompt.update(&app.repository);
-                    app.show_prompt(Prompt::TagSelect(prompt));
+                    app.show_prompt(prompts::remove_task_tag(task_id));
+                    app.awake_prompt();
                 }
             }
             _ => {}
@@ -314,3 +971,106 @@ fn handle_main_key(key: KeyEvent, app: &mut App) -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+fn handle_entries_action(action: Action, app: &mut App) -> anyhow::Result<()> {
+    if let Some(entries) = app.state.explorer.entries.as_mut() {
+        match action {
+            Action::EntryPrevious => {
+                entries.previous();
+            }
+            Action::EntryNext => {
+                entries.next();
+            }
+            Action::EntryFirst => {
+                entries.first();
+            }
+            Action::EntryLast => {
+                entries.last();
+            }
+            Action::FocusTasks => {
+                app.state.focus = Pane::Main;
+            }
+            Action::EntryDelete => {
+                if let Some(id) = entries.selected_raw().cloned() {
+                    app.show_prompt(prompts::delete_entry(id));
+                }
+            }
+            Action::EntryEditStart => {
+                if let Some(entry) = entries.selected::<Entry>(&app.repository) {
+                    let prompt =
+                        prompts::edit_entry_start(entry.id).suggest(time::format(entry.start));
+                    app.show_prompt(prompt);
+                }
+            }
+            Action::EntryEditEnd => {
+                if let Some(entry) = entries.selected::<Entry>(&app.repository) {
+                    let mut prompt = prompts::edit_entry_end(entry.id);
+                    if let Some(end) = entry.end {
+                        prompt = prompt.suggest(time::format(end));
+                    }
+                    app.show_prompt(prompt);
+                }
+            }
+            Action::EntrySplit => {
+                if let Some(id) = entries.selected_raw().cloned() {
+                    app.show_prompt(prompts::split_entry(id));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn handle_reports_action(action: Action, app: &mut App) {
+    let reports = &mut app.state.reports;
+    match action {
+        Action::ReportPrevious => reports.offset -= 1,
+        Action::ReportNext => reports.offset += 1,
+        Action::ReportCurrent => reports.offset = 0,
+        Action::ReportPeriod => {
+            reports.period = reports.period.cycle();
+            reports.offset = 0;
+        }
+        Action::ReportGrouping => reports.grouping = reports.grouping.cycle(),
+        _ => return,
+    }
+    reports.invalidate();
+    reports.refresh(&app.repository, &app.settings);
+}
+
+fn handle_stats_action(action: Action, app: &mut App) {
+    let stats = &mut app.state.stats;
+    match action {
+        Action::StatsPrevious => stats.offset -= 1,
+        Action::StatsNext => stats.offset += 1,
+        Action::StatsCurrent => stats.offset = 0,
+        _ => {}
+    }
+}
+
+fn handle_calendar_action(action: Action, app: &mut App) {
+    let calendar = &mut app.state.calendar;
+    match action {
+        Action::CalendarPreviousWeek => calendar.shift(-7),
+        Action::CalendarNextWeek => calendar.shift(7),
+        Action::CalendarPreviousDay => calendar.shift(-1),
+        Action::CalendarNextDay => calendar.shift(1),
+        Action::CalendarToday => calendar.today(),
+        _ => {}
+    }
+}
+
+fn handle_timeline_action(action: Action, app: &mut App) {
+    let day = app.state.timeline.day();
+    let count = timeline::slots(&app.repository, day, Local::now()).len();
+    let timeline = &mut app.state.timeline;
+    match action {
+        Action::TimelinePrevious => timeline.select_previous(),
+        Action::TimelineNext => timeline.select_next(count),
+        Action::TimelinePreviousDay => timeline.shift(-1),
+        Action::TimelineNextDay => timeline.shift(1),
+        Action::TimelineToday => timeline.today(),
+        _ => {}
+    }
+}
@@ -0,0 +1,714 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Context as _};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::app::Pane;
+
+/// Where a binding applies. Lookups go through the focused context first, then
+/// its parents (`Global` for panes, `Prompt` for prompts).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Context {
+    Global,
+    ProjectExplorer,
+    Tasks,
+    Entries,
+    Reports,
+    Stats,
+    Calendar,
+    Timeline,
+    Prompt,
+    InputPrompt,
+    SelectPrompt,
+    TagSelectPrompt,
+    Help,
+}
+
+impl Context {
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Global => "Global",
+            Self::ProjectExplorer => "Projects",
+            Self::Tasks => "Tasks",
+            Self::Entries => "Entries",
+            Self::Reports => "Reports",
+            Self::Stats => "Stats",
+            Self::Calendar => "Calendar",
+            Self::Timeline => "Timeline",
+            Self::Prompt => "Prompt",
+            Self::InputPrompt => "Input",
+            Self::SelectPrompt => "Selection",
+            Self::TagSelectPrompt => "Tags",
+            Self::Help => "Help",
+        }
+    }
+
+    /// Context whose bindings also apply underneath this one.
+    pub fn parent(&self) -> Option<Context> {
+        match self {
+            Self::Global | Self::Prompt => None,
+            Self::ProjectExplorer
+            | Self::Tasks
+            | Self::Entries
+            | Self::Reports
+            | Self::Stats
+            | Self::Calendar
+            | Self::Timeline => Some(Self::Global),
+            Self::InputPrompt | Self::SelectPrompt | Self::Help => Some(Self::Prompt),
+            Self::TagSelectPrompt => Some(Self::SelectPrompt),
+        }
+    }
+
+    /// Whether the bindings of `other` apply in this context.
+    pub fn inherits(&self, other: Context) -> bool {
+        *self == other || self.parent().is_some_and(|parent| parent.inherits(other))
+    }
+
+    pub fn of_pane(pane: &Pane) -> [Context; 2] {
+        let context = match pane {
+            Pane::ProjectExplorer => Self::ProjectExplorer,
+            Pane::Main => Self::Tasks,
+            Pane::Entries => Self::Entries,
+            Pane::Reports => Self::Reports,
+            Pane::Stats => Self::Stats,
+            Pane::Calendar => Self::Calendar,
+            Pane::Timeline => Self::Timeline,
+        };
+        [context, Self::Global]
+    }
+}
+
+macro_rules! actions {
+    ($($context:ident { $($action:ident: $name:literal, $description:literal, [$($key:literal),*];)* })*) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub enum Action {
+            $($($action,)*)*
+        }
+
+        impl Action {
+            pub const ALL: &'static [Action] = &[$($(Action::$action,)*)*];
+
+            /// Name used in the `[keybindings]` section of the config file.
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $($(Action::$action => $name,)*)*
+                }
+            }
+
+            pub fn description(&self) -> &'static str {
+                match self {
+                    $($(Action::$action => $description,)*)*
+                }
+            }
+
+            pub fn context(&self) -> Context {
+                match self {
+                    $($(Action::$action => Context::$context,)*)*
+                }
+            }
+
+            fn default_keys(&self) -> &'static [&'static str] {
+                match self {
+                    $($(Action::$action => &[$($key),*],)*)*
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    Global {
+        Quit: "quit", "quit", ["q"];
+        Help: "help.open", "list bindings", ["?"];
+        Undo: "undo", "undo last change", ["u"];
+        Redo: "redo", "redo last undone change", ["ctrl+r"];
+        Back: "back", "go back, quit from projects", ["esc"];
+        Open: "open", "open selected project or day, fill selected gap", ["enter"];
+        CollapseExplorer: "explorer.collapse", "hide projects", ["<"];
+        ExpandExplorer: "explorer.expand", "show projects", [">"];
+        ToggleArchived: "archived.toggle", "show or hide archived", ["H"];
+        Search: "search", "search tasks in all projects", ["/"];
+        ReportsOpen: "reports.open", "show reports", ["R"];
+        StatsOpen: "stats.open", "show charts", ["C"];
+        CalendarOpen: "calendar.open", "show calendar", ["Y"];
+        TimelineOpen: "timeline.open", "show timeline of the day", ["L"];
+        TimerPick: "timer.pick", "start timer on any task", ["ctrl+s"];
+    }
+    ProjectExplorer {
+        ExplorerPrevious: "explorer.previous", "previous project", ["up", "k"];
+        ExplorerNext: "explorer.next", "next project", ["down", "j"];
+        ExplorerFirst: "explorer.first", "first project", ["home", "gg"];
+        ExplorerLast: "explorer.last", "last project", ["end", "G"];
+        ProjectNew: "project.new", "new project", ["N"];
+        ProjectRename: "project.rename", "rename project", ["e"];
+        ProjectArchive: "project.archive", "archive or unarchive project", ["a"];
+        ProjectDelete: "project.delete", "delete project", ["D"];
+        ProjectAddDefaultTag: "project.add_default_tag", "add default tag", ["t"];
+        ProjectRemoveDefaultTag: "project.remove_default_tag", "remove default tag", ["T"];
+        ProjectApplyDefaultTags: "project.apply_default_tags", "apply default tags to tasks", ["A"];
+    }
+    Tasks {
+        TaskPrevious: "task.previous", "previous task", ["up", "k"];
+        TaskNext: "task.next", "next task", ["down", "j"];
+        TaskFirst: "task.first", "first task", ["home", "gg"];
+        TaskLast: "task.last", "last task", ["end", "G"];
+        TaskNew: "task.new", "new task", ["N"];
+        TaskRename: "task.rename", "rename task", ["e"];
+        TaskEditNotes: "task.edit_notes", "edit notes", ["i"];
+        TaskMove: "task.move", "move to another project", ["m"];
+        TaskArchive: "task.archive", "archive or unarchive task", ["a"];
+        TaskDelete: "task.delete", "delete task", ["D"];
+        TaskAddTag: "task.add_tag", "add tag", ["t"];
+        TaskRemoveTag: "task.remove_tag", "remove tag", ["T"];
+        TaskAddEntry: "task.add_entry", "add past entry", ["E"];
+        TimerStart: "timer.start", "start timer", ["s"];
+        TimerStop: "timer.stop", "stop timer", ["S"];
+        TimerToggle: "timer.toggle", "start or stop timer", ["space"];
+        FocusEntries: "task.focus_entries", "focus entries", ["tab"];
+    }
+    Entries {
+        EntryPrevious: "entry.previous", "previous entry", ["up", "k"];
+        EntryNext: "entry.next", "next entry", ["down", "j"];
+        EntryFirst: "entry.first", "first entry", ["home", "gg"];
+        EntryLast: "entry.last", "last entry", ["end", "G"];
+        EntryDelete: "entry.delete", "delete entry", ["D"];
+        EntryEditStart: "entry.edit_start", "edit start", ["s"];
+        EntryEditEnd: "entry.edit_end", "edit end", ["e"];
+        EntrySplit: "entry.split", "split in two", ["x"];
+        FocusTasks: "entry.focus_tasks", "focus tasks", ["tab"];
+    }
+    Reports {
+        ReportPrevious: "report.previous", "previous period", ["left", "h"];
+        ReportNext: "report.next", "next period", ["right", "l"];
+        ReportCurrent: "report.current", "current period", ["t"];
+        ReportPeriod: "report.period", "switch between days, weeks and months", ["p"];
+        ReportGrouping: "report.grouping", "group by project, task or tag", ["b"];
+    }
+    Stats {
+        StatsPrevious: "stats.previous", "earlier days", ["left", "h"];
+        StatsNext: "stats.next", "later days", ["right", "l"];
+        StatsCurrent: "stats.current", "up to today", ["t"];
+    }
+    Calendar {
+        CalendarPreviousWeek: "calendar.previous_week", "previous week", ["left", "h"];
+        CalendarNextWeek: "calendar.next_week", "next week", ["right", "l"];
+        CalendarPreviousDay: "calendar.previous_day", "previous day", ["up", "k"];
+        CalendarNextDay: "calendar.next_day", "next day", ["down", "j"];
+        CalendarToday: "calendar.today", "today", ["t"];
+    }
+    Timeline {
+        TimelinePrevious: "timeline.previous", "previous entry or gap", ["up", "k"];
+        TimelineNext: "timeline.next", "next entry or gap", ["down", "j"];
+        TimelinePreviousDay: "timeline.previous_day", "previous day", ["left", "h"];
+        TimelineNextDay: "timeline.next_day", "next day", ["right", "l"];
+        TimelineToday: "timeline.today", "today", ["t"];
+    }
+    Prompt {
+        PromptCancel: "prompt.cancel", "cancel", ["esc"];
+        PromptConfirm: "prompt.confirm", "continue", ["enter"];
+        PromptHelp: "prompt.help", "list bindings", ["f1"];
+    }
+    InputPrompt {
+        InputDelete: "input.delete", "delete previous character", ["backspace"];
+        InputDeleteNext: "input.delete_next", "delete next character", ["delete"];
+        InputDeleteWord: "input.delete_word", "delete previous word", ["ctrl+w"];
+        InputLeft: "input.left", "move left", ["left"];
+        InputRight: "input.right", "move right", ["right"];
+        InputWordLeft: "input.word_left", "move to previous word", ["ctrl+left"];
+        InputWordRight: "input.word_right", "move to next word", ["ctrl+right"];
+        InputHome: "input.home", "move to start", ["home", "ctrl+a"];
+        InputEnd: "input.end", "move to end", ["end", "ctrl+e"];
+    }
+    SelectPrompt {
+        SelectPrevious: "select.previous", "up", ["up", "ctrl+k"];
+        SelectNext: "select.next", "down", ["down", "ctrl+j"];
+        SelectDelete: "select.delete", "delete search character", ["backspace"];
+        SelectMark: "select.mark", "mark to select several", ["space"];
+    }
+    TagSelectPrompt {
+        SelectNewTag: "select.new_tag", "create tag", ["ctrl+n"];
+        SelectRenameTag: "select.rename_tag", "rename tag", ["ctrl+e"];
+        SelectDeleteTag: "select.delete_tag", "delete tag", ["ctrl+d"];
+    }
+    Help {
+        HelpUp: "help.up", "scroll up", ["up", "k"];
+        HelpDown: "help.down", "scroll down", ["down", "j"];
+    }
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Action> {
+        Self::ALL
+            .iter()
+            .find(|action| action.name() == name)
+            .cloned()
+    }
+}
+
+/// A key press, normalized so that shifted characters only carry their case.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = value.split('+').collect();
+        // Allow binding the `+` key itself
+        if value.ends_with("++") || value == "+" {
+            parts.pop();
+            parts.pop();
+            parts.push("+");
+        }
+        let (key, modifier_names) = parts.split_last().ok_or_else(|| anyhow!("Empty key"))?;
+
+        let mut modifiers = KeyModifiers::empty();
+        for modifier in modifier_names {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("Unknown modifier `{}` in `{}`", modifier, value),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(ch), None) => {
+                if modifiers.contains(KeyModifiers::SHIFT) {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                    KeyCode::Char(ch.to_ascii_uppercase())
+                } else {
+                    KeyCode::Char(ch)
+                }
+            }
+            _ => match key.to_lowercase().as_str() {
+                "esc" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                    KeyCode::BackTab
+                }
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "space" => KeyCode::Char(' '),
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => bail!("Unknown key `{}` in `{}`", key, value),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(ch) => write!(f, "{}", ch),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("shift+tab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Insert => f.write_str("insert"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            _ => f.write_str("?"),
+        }
+    }
+}
+
+/// Keys to press one after the other, such as `gg`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sequence(pub Vec<Key>);
+
+impl Sequence {
+    fn starts_with(&self, keys: &[Key]) -> bool {
+        self.0.starts_with(keys)
+    }
+}
+
+impl FromStr for Sequence {
+    type Err = anyhow::Error;
+
+    /// Keys are separated by spaces (`ctrl+x ctrl+s`), plain characters may
+    /// also be written together (`gg`).
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.contains(' ') {
+            let keys = value
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<anyhow::Result<_>>()?;
+            return Ok(Self(keys));
+        }
+        match value.parse() {
+            Ok(key) => Ok(Self(vec![key])),
+            Err(_) if !value.contains('+') && value.chars().count() > 1 => {
+                let keys = value
+                    .chars()
+                    .map(|ch| ch.to_string().parse())
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(Self(keys))
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl fmt::Display for Sequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let plain = self.0.iter().all(|key| {
+            key.modifiers.is_empty() && matches!(key.code, KeyCode::Char(ch) if ch != ' ')
+        });
+        let separator = if plain { "" } else { " " };
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        f.write_str(&keys.join(separator))
+    }
+}
+
+/// Value of an entry of the `[keybindings]` config section. Dotted action
+/// names may be written as nested tables, e.g. `[keybindings.task]`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BindingConfig {
+    Key(String),
+    Keys(Vec<String>),
+    Group(HashMap<String, BindingConfig>),
+}
+
+impl BindingConfig {
+    fn flatten(&self, name: String, out: &mut Vec<(String, Vec<String>)>) {
+        match self {
+            Self::Key(key) => out.push((name, vec![key.clone()])),
+            Self::Keys(keys) => out.push((name, keys.clone())),
+            Self::Group(group) => {
+                for (child, config) in group {
+                    config.flatten(format!("{}.{}", name, child), out);
+                }
+            }
+        }
+    }
+}
+
+/// Result of feeding a key to [`Bindings::resolve`].
+pub enum Resolution {
+    Action(Action),
+    /// The keys pressed so far start a sequence, wait for the next one.
+    Pending,
+    Unbound,
+}
+
+/// Keys bound to each action. Handlers, prompt footers and the help overlay all
+/// go through this registry so they can't disagree.
+pub struct Bindings {
+    keys: HashMap<Action, Vec<Sequence>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let keys = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|key| key.parse().expect("Invalid default binding"))
+                    .collect();
+                (*action, keys)
+            })
+            .collect();
+        Self { keys }
+    }
+}
+
+impl Bindings {
+    /// Default bindings with the actions of the config file replaced. Fails on
+    /// unknown actions, invalid keys or conflicting bindings.
+    pub fn new(config: &HashMap<String, BindingConfig>) -> anyhow::Result<Self> {
+        let mut overrides = Vec::new();
+        for (name, config) in config {
+            config.flatten(name.clone(), &mut overrides);
+        }
+
+        let mut bindings = Self::default();
+        for (name, keys) in overrides {
+            let action =
+                Action::from_name(&name).ok_or_else(|| anyhow!("Unknown action `{}`", name))?;
+            let keys = keys
+                .iter()
+                .map(|key| key.parse())
+                .collect::<anyhow::Result<_>>()
+                .with_context(|| format!("Invalid key for `{}`", name))?;
+            bindings.keys.insert(action, keys);
+        }
+
+        let conflicts = bindings.conflicts();
+        if !conflicts.is_empty() {
+            bail!("Conflicting keybindings:\n  {}", conflicts.join("\n  "));
+        }
+        Ok(bindings)
+    }
+
+    /// Bindings which can't be told apart because they are equal or one starts
+    /// the other, in the same context or in a context and one it inherits.
+    fn conflicts(&self) -> Vec<String> {
+        let bound: Vec<(&Action, &Sequence)> = Action::ALL
+            .iter()
+            .flat_map(|action| self.keys(action).iter().map(move |keys| (action, keys)))
+            .collect();
+
+        let mut conflicts = Vec::new();
+        for (idx, (action, keys)) in bound.iter().enumerate() {
+            for (other, other_keys) in &bound[idx + 1..] {
+                let related = action.context().inherits(other.context())
+                    || other.context().inherits(action.context());
+                if action == other || !related {
+                    continue;
+                }
+                if keys.starts_with(&other_keys.0) || other_keys.starts_with(&keys.0) {
+                    conflicts.push(format!(
+                        "`{}` ({}) and `{}` ({})",
+                        keys,
+                        action.name(),
+                        other_keys,
+                        other.name()
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Feeds a key to the keys pressed so far and finds the action bound to
+    /// them, trying contexts in order. A key which breaks a sequence is tried
+    /// on its own.
+    pub fn resolve(&self, contexts: &[Context], pending: &mut Vec<Key>, key: Key) -> Resolution {
+        pending.push(key);
+        match self.find(contexts, pending) {
+            Resolution::Pending => Resolution::Pending,
+            Resolution::Unbound if pending.len() > 1 => {
+                pending.clear();
+                self.resolve(contexts, pending, key)
+            }
+            resolution => {
+                pending.clear();
+                resolution
+            }
+        }
+    }
+
+    fn find(&self, contexts: &[Context], pressed: &[Key]) -> Resolution {
+        let mut resolution = Resolution::Unbound;
+        for context in contexts {
+            for action in Action::ALL
+                .iter()
+                .filter(|action| action.context() == *context)
+            {
+                for keys in self.keys(action) {
+                    if keys.0 == pressed {
+                        return Resolution::Action(*action);
+                    } else if keys.starts_with(pressed) {
+                        resolution = Resolution::Pending;
+                    }
+                }
+            }
+        }
+        resolution
+    }
+
+    pub fn keys(&self, action: &Action) -> &[Sequence] {
+        self.keys.get(action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Actions of a context with their keys, skipping unbound ones.
+    pub fn list(&self, context: Context) -> Vec<(String, &'static str)> {
+        Action::ALL
+            .iter()
+            .filter(|action| action.context() == context)
+            .filter_map(|action| self.hint(action))
+            .collect()
+    }
+
+    /// Short hints for prompt footers, showing the first key of each action.
+    pub fn hints(&self, actions: &[Action]) -> Vec<(String, &'static str)> {
+        actions
+            .iter()
+            .filter_map(|action| {
+                self.keys(action)
+                    .first()
+                    .map(|keys| (keys.to_string(), action.description()))
+            })
+            .collect()
+    }
+
+    fn hint(&self, action: &Action) -> Option<(String, &'static str)> {
+        let keys = self.keys(action);
+        if keys.is_empty() {
+            return None;
+        }
+        let keys: Vec<String> = keys.iter().map(Sequence::to_string).collect();
+        Some((keys.join(", "), action.description()))
+    }
+
+    /// Commented `[keybindings]` section listing every action with its default keys.
+    pub fn default_config() -> String {
+        let mut config = String::new();
+        for action in Action::ALL {
+            let keys: Vec<String> = action
+                .default_keys()
+                .iter()
+                .map(|key| format!("{:?}", key))
+                .collect();
+            let keys = match keys.as_slice() {
+                [key] => key.clone(),
+                keys => format!("[{}]", keys.join(", ")),
+            };
+            config.push_str(&format!("#{} = {}\n", action.name(), keys));
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(bindings: &[(&str, &str)]) -> HashMap<String, BindingConfig> {
+        bindings
+            .iter()
+            .map(|(name, key)| (name.to_string(), BindingConfig::Key(key.to_string())))
+            .collect()
+    }
+
+    #[test]
+    fn keys_round_trip() {
+        for key in [
+            "q",
+            "G",
+            "ctrl+r",
+            "alt+x",
+            "space",
+            "enter",
+            "shift+tab",
+            "f5",
+            "+",
+        ] {
+            assert_eq!(key.parse::<Key>().unwrap().to_string(), key);
+        }
+        assert_eq!("ctrl++".parse::<Key>().unwrap().to_string(), "ctrl++");
+        assert_eq!("shift+g".parse::<Key>().unwrap().to_string(), "G");
+        assert_eq!(
+            "Ctrl+Enter".parse::<Key>().unwrap().to_string(),
+            "ctrl+enter"
+        );
+    }
+
+    #[test]
+    fn plus_key() {
+        let plus = Key {
+            code: KeyCode::Char('+'),
+            modifiers: KeyModifiers::empty(),
+        };
+        assert_eq!("+".parse::<Key>().unwrap(), plus);
+        assert_eq!(
+            "ctrl++".parse::<Key>().unwrap(),
+            Key {
+                modifiers: KeyModifiers::CONTROL,
+                ..plus
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_keys() {
+        for key in ["", "hyper+x", "ctrl+", "f13", "escape", "ctrl+gg"] {
+            assert!(key.parse::<Key>().is_err(), "`{}` parsed", key);
+        }
+        assert!("ctrl+x nope".parse::<Sequence>().is_err());
+    }
+
+    #[test]
+    fn sequences_round_trip() {
+        let gg = "gg".parse::<Sequence>().unwrap();
+        assert_eq!(gg.0, vec!["g".parse::<Key>().unwrap(); 2]);
+        assert_eq!(gg, "g g".parse().unwrap());
+        for sequence in ["gg", "q", "ctrl+x ctrl+s", "g space"] {
+            assert_eq!(sequence.parse::<Sequence>().unwrap().to_string(), sequence);
+        }
+    }
+
+    #[test]
+    fn defaults_do_not_conflict() {
+        assert!(Bindings::default().conflicts().is_empty());
+        assert!(Bindings::new(&HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn prefix_conflicts_with_parent_context() {
+        // `task.first` is bound to `gg` in `Tasks`, which inherits `Global`
+        let err = Bindings::new(&config(&[("quit", "g")])).err().unwrap();
+        assert!(err.to_string().contains("`g` (quit) and `gg` (task.first)"));
+        assert!(Bindings::new(&config(&[("quit", "gg")])).is_err());
+    }
+
+    #[test]
+    fn sibling_contexts_do_not_conflict() {
+        assert!(Bindings::new(&config(&[("task.first", "z"), ("entry.first", "z")])).is_ok());
+    }
+
+    #[test]
+    fn unknown_actions_and_keys() {
+        assert!(Bindings::new(&config(&[("nope", "x")])).is_err());
+        assert!(Bindings::new(&config(&[("quit", "hyper+q")])).is_err());
+    }
+}
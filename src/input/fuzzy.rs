@@ -0,0 +1,102 @@
+/// Characters of a text matched by a search, in order.
+pub struct Match {
+    /// Higher for consecutive matches and matches at the start of words.
+    pub score: i64,
+    /// Indices of the matched characters.
+    pub indices: Vec<usize>,
+}
+
+/// Matches the characters of the pattern in order anywhere in the text,
+/// ignoring case, keeping the best scoring match.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<Match> {
+    let pattern: Vec<char> = pattern.chars().map(lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.iter().copied().map(lowercase).collect();
+    let first = match pattern.first() {
+        Some(first) => *first,
+        None => {
+            return Some(Match {
+                score: 0,
+                indices: Vec::new(),
+            })
+        }
+    };
+    (0..lower.len())
+        .filter(|start| lower[*start] == first)
+        .filter_map(|start| match_from(&pattern, &text, &lower, start))
+        .max_by_key(|found| found.score)
+}
+
+fn lowercase(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+fn match_from(pattern: &[char], text: &[char], lower: &[char], start: usize) -> Option<Match> {
+    let mut indices = Vec::with_capacity(pattern.len());
+    let mut position = start;
+    for ch in pattern {
+        let offset = lower
+            .get(position..)?
+            .iter()
+            .position(|other| other == ch)?;
+        indices.push(position + offset);
+        position += offset + 1;
+    }
+
+    let mut score = 0;
+    for (i, index) in indices.iter().enumerate() {
+        score += 1;
+        if *index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if i > 0 && indices[i - 1] + 1 == *index {
+            score += 5;
+        }
+    }
+    let span = indices[indices.len() - 1] - indices[0] + 1;
+    score -= (span - indices.len()) as i64;
+    Some(Match { score, indices })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(pattern: &str, text: &str) -> i64 {
+        fuzzy_match(pattern, text).unwrap().score
+    }
+
+    #[test]
+    fn prefix_beats_scattered_match() {
+        assert!(score("doc", "documentation") > score("doc", "dead october cats"));
+        assert!(score("doc", "documentation") > score("doc", "adobe cloud"));
+        assert!(score("rev", "review code") > score("rev", "reserve"));
+    }
+
+    #[test]
+    fn picks_best_occurrence() {
+        let found = fuzzy_match("ab", "a xab").unwrap();
+        assert_eq!(found.indices, vec![3, 4]);
+    }
+
+    #[test]
+    fn ignores_case() {
+        let found = fuzzy_match("DoC", "my Documents").unwrap();
+        assert_eq!(found.indices, vec![3, 4, 5]);
+        assert_eq!(score("doc", "DOCS"), score("DOC", "docs"));
+    }
+
+    #[test]
+    fn no_match() {
+        assert!(fuzzy_match("xyz", "documentation").is_none());
+        assert!(fuzzy_match("cod", "doc").is_none());
+        assert!(fuzzy_match("a", "").is_none());
+    }
+
+    #[test]
+    fn empty_pattern_matches_everything() {
+        let found = fuzzy_match("", "anything").unwrap();
+        assert_eq!(found.score, 0);
+        assert!(found.indices.is_empty());
+    }
+}
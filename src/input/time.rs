@@ -0,0 +1,242 @@
+use anyhow::{anyhow, bail};
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+
+/// Format of the times suggested in prompts, which [`parse`] reads back.
+const SUGGEST_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+pub fn format(time: DateTime<Local>) -> String {
+    time.format(SUGGEST_FORMAT).to_string()
+}
+
+/// Parses a point in time, either relative to `now` ("now", "-45m", "+1h",
+/// "2h ago", "1h30m ago") or a time of day, optionally preceded by the day
+/// ("9:30", "2pm", "yesterday 14:00", "mon 9:00", "2024-03-01 17:15").
+pub fn parse(text: &str, now: DateTime<Local>) -> anyhow::Result<DateTime<Local>> {
+    let text = text.trim().to_lowercase();
+    if text.is_empty() {
+        bail!("Type a time such as `9:30`, `yesterday 14:00` or `45m ago`");
+    }
+    if text == "now" {
+        return Ok(now);
+    }
+    if let Some(offset) = parse_offset(&text)? {
+        return now
+            .checked_add_signed(offset)
+            .ok_or_else(|| anyhow!("`{}` is too far away", text));
+    }
+
+    let today = now.date_naive();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let (day, time) = match words.as_slice() {
+        [word] if parse_day(word, today).is_ok() => bail!("Missing the time after `{}`", word),
+        [time] => (today, parse_time(time)?),
+        [day, time] => (parse_day(day, today)?, parse_time(time)?),
+        _ => bail!("Expected a day and a time, such as `yesterday 14:00`"),
+    };
+    local(day.and_time(time))
+}
+
+/// Signed offset from now, if the text is one.
+fn parse_offset(text: &str) -> anyhow::Result<Option<Duration>> {
+    let (sign, duration) = if let Some(duration) = text.strip_suffix("ago") {
+        (-1, duration.trim_end())
+    } else if let Some(duration) = text.strip_prefix('-') {
+        (-1, duration.trim_start())
+    } else if let Some(duration) = text.strip_prefix('+') {
+        (1, duration.trim_start())
+    } else {
+        return Ok(None);
+    };
+    if duration.is_empty() {
+        bail!("Missing a duration, such as `45m` or `1h30m`");
+    }
+    Ok(Some(parse_duration(duration)? * sign))
+}
+
+/// Reads numbers followed by units, such as `1h30m` or `2 days`.
+fn parse_duration(text: &str) -> anyhow::Result<Duration> {
+    let too_long = || anyhow!("`{}` is too long", text);
+    let mut minutes: i64 = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if digits == 0 {
+            bail!("Expected a number in `{}`", text);
+        }
+        let amount: i64 = rest[..digits].parse().map_err(|_| too_long())?;
+        rest = rest[digits..].trim_start();
+        let letters = rest
+            .find(|ch: char| !ch.is_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..letters] {
+            "m" | "min" | "mins" | "minute" | "minutes" => 1,
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60,
+            "d" | "day" | "days" => 24 * 60,
+            "" => bail!(
+                "Missing the unit after `{}`, such as `m`, `h` or `d`",
+                amount
+            ),
+            unit => bail!("Unknown unit `{}`, use `m`, `h` or `d`", unit),
+        };
+        minutes = amount
+            .checked_mul(unit)
+            .and_then(|amount| minutes.checked_add(amount))
+            .ok_or_else(too_long)?;
+        rest = rest[letters..].trim_start();
+    }
+    Duration::try_minutes(minutes).ok_or_else(too_long)
+}
+
+fn parse_day(word: &str, today: NaiveDate) -> anyhow::Result<NaiveDate> {
+    match word {
+        "today" => return Ok(today),
+        "yesterday" => return Ok(today - Duration::days(1)),
+        _ => {}
+    }
+    // The last such day, today included
+    if let Ok(weekday) = word.parse::<Weekday>() {
+        let days =
+            (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        return Ok(today - Duration::days(i64::from(days)));
+    }
+    NaiveDate::parse_from_str(word, "%Y-%m-%d").map_err(|_| {
+        anyhow!(
+            "Unknown day `{}`, use `today`, `yesterday`, a weekday or a date such as `2024-03-01`",
+            word
+        )
+    })
+}
+
+/// Reads a time of day on 24 hours, or on 12 hours when followed by `am` or `pm`.
+fn parse_time(word: &str) -> anyhow::Result<NaiveTime> {
+    let invalid = || anyhow!("Invalid time `{}`, use `9:30`, `14:00` or `2pm`", word);
+    let (clock, afternoon) = match (word.strip_suffix("am"), word.strip_suffix("pm")) {
+        (Some(clock), _) => (clock, Some(false)),
+        (_, Some(clock)) => (clock, Some(true)),
+        _ => (word, None),
+    };
+    let (hour, minute) = clock.split_once(':').unwrap_or((clock, "00"));
+    if hour.is_empty() || minute.len() != 2 {
+        return Err(invalid());
+    }
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    let hour = match afternoon {
+        Some(afternoon) if (1..=12).contains(&hour) => hour % 12 + if afternoon { 12 } else { 0 },
+        Some(_) => return Err(invalid()),
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0).ok_or_else(invalid)
+}
+
+/// Times repeated when clocks go back are taken the first time around, times
+/// skipped when they go forward are refused.
+fn local(time: NaiveDateTime) -> anyhow::Result<DateTime<Local>> {
+    match Local.from_local_datetime(&time) {
+        LocalResult::Single(time) => Ok(time),
+        // Not necessarily in order
+        LocalResult::Ambiguous(first, second) => Ok(first.min(second)),
+        LocalResult::None => bail!("`{}` does not exist in the local time zone", time),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Local time in a zone with daylight saving time, whatever the zone of
+    /// the machine running the tests.
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+        Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .earliest()
+            .unwrap()
+    }
+
+    /// Wednesday afternoon.
+    fn now() -> DateTime<Local> {
+        at(2024, 3, 6, 15, 0)
+    }
+
+    fn parsed(text: &str) -> DateTime<Local> {
+        parse(text, now()).unwrap()
+    }
+
+    #[test]
+    fn now_and_offsets() {
+        assert_eq!(parsed("now"), now());
+        assert_eq!(parsed(" NOW "), now());
+        assert_eq!(parsed("-45m"), at(2024, 3, 6, 14, 15));
+        assert_eq!(parsed("+1h"), at(2024, 3, 6, 16, 0));
+        assert_eq!(parsed("1h30m ago"), at(2024, 3, 6, 13, 30));
+        assert_eq!(parsed("2 days ago"), at(2024, 3, 4, 15, 0));
+        assert_eq!(parsed("- 1 hour 5 min"), at(2024, 3, 6, 13, 55));
+    }
+
+    #[test]
+    fn times_of_today() {
+        assert_eq!(parsed("9:30"), at(2024, 3, 6, 9, 30));
+        assert_eq!(parsed("14"), at(2024, 3, 6, 14, 0));
+        assert_eq!(parsed("2pm"), at(2024, 3, 6, 14, 0));
+        assert_eq!(parsed("2:45PM"), at(2024, 3, 6, 14, 45));
+        assert_eq!(parsed("12am"), at(2024, 3, 6, 0, 0));
+        assert_eq!(parsed("12pm"), at(2024, 3, 6, 12, 0));
+    }
+
+    #[test]
+    fn times_of_other_days() {
+        assert_eq!(parsed("yesterday 14:00"), at(2024, 3, 5, 14, 0));
+        assert_eq!(parsed("today 8am"), at(2024, 3, 6, 8, 0));
+        assert_eq!(parsed("mon 9:00"), at(2024, 3, 4, 9, 0));
+        assert_eq!(parsed("thursday 9:00"), at(2024, 2, 29, 9, 0));
+        // The current weekday is today, not a week ago
+        assert_eq!(parsed("wed 9:00"), at(2024, 3, 6, 9, 0));
+        assert_eq!(parsed("2024-03-01 17:15"), at(2024, 3, 1, 17, 15));
+    }
+
+    #[test]
+    fn suggestions_parse_back() {
+        let time = at(2024, 2, 29, 23, 59);
+        assert_eq!(parse(&format(time), now()).unwrap(), time);
+    }
+
+    #[test]
+    fn invalid_times() {
+        for text in [
+            "",
+            "yesterday",
+            "tomorrow 9:00",
+            "13pm",
+            "0am",
+            "9:5",
+            "25:00",
+            "-",
+            "ago",
+            "45 ago",
+            "45x ago",
+            "9:30 today now",
+        ] {
+            assert!(parse(text, now()).is_err(), "`{}` parsed", text);
+        }
+    }
+
+    #[test]
+    fn daylight_saving_time() {
+        // Clocks skip from 2:00 to 3:00
+        assert!(parse("2024-03-31 2:30", now()).is_err());
+        assert_eq!(
+            parsed("2024-03-31 3:30") - parsed("2024-03-31 1:30"),
+            Duration::hours(1)
+        );
+        // Clocks go back from 3:00 to 2:00, the earliest time is kept
+        let repeated = parsed("2024-10-27 2:30");
+        assert_eq!(repeated.offset().local_minus_utc(), 2 * 3600);
+        assert_eq!(parsed("2024-10-27 3:30") - repeated, Duration::hours(2));
+    }
+}
@@ -1,5 +1,8 @@
 use std::io;
+use std::time::Duration;
 
+use clap::Parser;
+use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
@@ -8,36 +11,67 @@ use tui::backend::{Backend, CrosstermBackend};
 use tui::Terminal;
 
 mod app;
+mod cli;
+mod config;
+mod history;
 mod input;
 mod model;
 mod prompts;
+mod report;
 mod storage;
 mod ui;
 
+/// Redraw interval while a timer is running.
+const TICK_RATE: Duration = Duration::from_secs(1);
+
 fn main() -> anyhow::Result<()> {
+    let cli = cli::Cli::parse();
+    if let Some(result) = cli.command.as_ref().and_then(cli::run_early) {
+        return result;
+    }
+    let settings = config::load_settings()?;
+    let database = config::database_path(cli.db, &settings)?;
+    let mut app = app::init(settings, &database)?;
+    if let Some(command) = cli.command {
+        return cli::run(&mut app, command, cli.format);
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
 
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = run(&mut terminal);
+    let result = run(&mut terminal, app);
 
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
     result
 }
 
-fn run<B: Backend>(terminal: &mut Terminal<B>) -> anyhow::Result<()> {
-    let mut app = app::init()?;
-
+fn run<B: Backend>(terminal: &mut Terminal<B>, mut app: app::App) -> anyhow::Result<()> {
     loop {
         terminal.draw(|frame| ui::draw_frame(frame, &app))?;
-        if input::handle_event(&mut app, event::read()?)? {
-            break;
+        let mut timeout = app.status_timeout();
+        if app.repository.running_entry().is_some() {
+            timeout = Some(timeout.map_or(TICK_RATE, |timeout| timeout.min(TICK_RATE)));
+        }
+        if let Some(timeout) = timeout {
+            if !event::poll(timeout)? {
+                continue;
+            }
+        }
+        match input::handle_event(&mut app, event::read()?) {
+            Ok(true) => break,
+            Ok(false) => {}
+            Err(error) => app.error(&error),
         }
     }
     Ok(())
@@ -1,3 +1,6 @@
+use chrono::{DateTime, Duration, Local};
+use serde::{Deserialize, Serialize};
+
 use crate::app::Repository;
 
 macro_rules! impl_id {
@@ -10,37 +13,76 @@ macro_rules! impl_id {
     };
 }
 
-impl_id!(TagId, ProjectId, TaskId);
+impl_id!(TagId, ProjectId, TaskId, EntryId);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TagId(pub u32);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Tag {
     pub id: TagId,
     pub name: String,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ProjectId(pub u32);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Project {
     pub id: ProjectId,
     pub name: String,
     pub default_tags: Vec<TagId>,
     pub tasks: Vec<TaskId>,
+
+    /// Hidden from the explorer along with its tasks, and no longer offered to
+    /// start timers on or to move tasks to.
+    #[serde(default)]
+    pub archived: bool,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TaskId(pub u32);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Task {
     pub id: TaskId,
     pub project_id: ProjectId,
     pub name: String,
     pub tags: Vec<TagId>,
+
+    /// Unknown for tasks created before it was recorded.
+    pub created_at: Option<DateTime<Local>>,
+
+    /// Hidden from the tasks of its project, and no longer offered to start
+    /// timers on.
+    #[serde(default)]
+    pub archived: bool,
+
+    /// Free text shown with the task and matched by searches.
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntryId(pub u32);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: EntryId,
+    pub task_id: TaskId,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+}
+
+impl Entry {
+    pub fn is_running(&self) -> bool {
+        self.end.is_none()
+    }
+
+    /// Time covered by the entry, counting up to `now` if it is still running.
+    pub fn duration(&self, now: DateTime<Local>) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
 }
 
 pub trait FromId<Id> {
@@ -65,6 +107,12 @@ impl From<&Task> for TaskId {
     }
 }
 
+impl From<&Entry> for EntryId {
+    fn from(entry: &Entry) -> Self {
+        entry.id
+    }
+}
+
 impl FromId<TagId> for Tag {
     fn from_id<'a>(id: &TagId, repository: &'a Repository) -> &'a Self {
         repository
@@ -91,3 +139,12 @@ impl FromId<TaskId> for Task {
             .expect("Repository is out of sync (tasks)")
     }
 }
+
+impl FromId<EntryId> for Entry {
+    fn from_id<'a>(id: &EntryId, repository: &'a Repository) -> &'a Self {
+        repository
+            .entries
+            .get(id)
+            .expect("Repository is out of sync (entries)")
+    }
+}
@@ -1,44 +1,217 @@
+use anyhow::bail;
+use chrono::{DateTime, Duration, Local};
+
+use crate::app::App;
+use crate::history::{self, Change, Operation};
 use crate::input::*;
 use crate::model::*;
+use crate::ui::util::format_duration;
+
+/// Counts items with the right form, e.g. `1 task` or `3 tasks`.
+pub fn count(count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        1 => format!("1 {}", singular),
+        _ => format!("{} {}", count, plural),
+    }
+}
+
+/// Describes a change to one or several items, e.g. `add tag` or `add tags`.
+fn describe(action: &str, count: usize, singular: &str, plural: &str) -> String {
+    match count {
+        1 => format!("{} {}", action, singular),
+        _ => format!("{} {}", action, plural),
+    }
+}
+
+/// What deleting the given tasks also removes, e.g. `3 tasks, 12h00 of entries`.
+fn tasks_summary(app: &App, tasks: &[TaskId]) -> String {
+    let now = Local::now();
+    let time = tasks.iter().fold(Duration::zero(), |total, task_id| {
+        total + app.repository.task_time(task_id, now)
+    });
+    format!(
+        "{}, {} of entries",
+        count(tasks.len(), "task", "tasks"),
+        format_duration(&app.settings, time)
+    )
+}
+
+/// Fails with a readable error when a name is taken, rather than the one of
+/// the UNIQUE constraint in the storage.
+fn check_unique<'a, I>(kind: &str, mut names: I, name: &str) -> anyhow::Result<()>
+where
+    I: Iterator<Item = &'a str>,
+{
+    if names.any(|other| other == name) {
+        bail!("{} `{}` already exists", kind, name);
+    }
+    Ok(())
+}
+
+fn check_tag_name(app: &App, name: &str, except: Option<TagId>) -> anyhow::Result<()> {
+    let names = app
+        .repository
+        .tags
+        .values()
+        .filter(|tag| Some(tag.id) != except)
+        .map(|tag| tag.name.as_str());
+    check_unique("Tag", names, name)
+}
+
+fn check_project_name(app: &App, name: &str, except: Option<ProjectId>) -> anyhow::Result<()> {
+    let names = app
+        .repository
+        .projects
+        .values()
+        .filter(|project| Some(project.id) != except)
+        .map(|project| project.name.as_str());
+    check_unique("Project", names, name)
+}
 
-pub fn new_tag() -> Prompt {
-    Prompt::Input(InputPrompt::new("New Tag", 15, true, |app, name| {
-        let tag = app.storage.create_tag(name)?;
-        app.repository.add_tag(tag);
+/// Prompt for a tag name starting with `name`, which goes through the same
+/// character filter and length limit as typed keys.
+pub fn new_tag(name: &str) -> Prompt {
+    let mut prompt = InputPrompt::new("New Tag", 15, true, |app, name| {
+        check_tag_name(app, &name, None)?;
+        let transaction = app.storage.transaction()?;
+        let tag = transaction.create_tag(name)?;
+        Change::new("create tag", vec![Operation::InsertTag(tag)])
+            .record(transaction, &mut app.repository)?;
         app.sync();
         Ok(())
-    }))
+    });
+    prompt.insert(name);
+    Prompt::Input(prompt)
 }
 
-pub fn delete_tag(tag_id: TagId) -> Prompt {
-    Prompt::Confirm(ConfirmPrompt::new("deleting selected tag", move |app| {
-        app.storage.delete_tag(&tag_id)?;
-        app.repository.remove_tag(&tag_id);
-        app.sync();
+pub fn rename_tag(tag_id: TagId) -> Prompt {
+    Prompt::Input(InputPrompt::new(
+        "Rename Tag",
+        15,
+        true,
+        move |app, name| {
+            check_tag_name(app, &name, Some(tag_id))?;
+            let from = Tag::from_id(&tag_id, &app.repository).name.clone();
+            app.execute(Change::new(
+                "rename tag",
+                vec![Operation::RenameTag {
+                    id: tag_id,
+                    from,
+                    to: name,
+                }],
+            ))?;
+            Ok(())
+        },
+    ))
+}
+
+pub fn delete_tag(app: &App, tag_id: TagId) -> Prompt {
+    let tasks = app
+        .repository
+        .tasks
+        .values()
+        .filter(|task| task.tags.contains(&tag_id))
+        .count();
+    let action = format!(
+        "deleting selected tag (used by {})",
+        count(tasks, "task", "tasks")
+    );
+    Prompt::Confirm(ConfirmPrompt::new(action, move |app| {
+        let operations = history::delete_tag(&app.repository, &tag_id);
+        app.execute(Change::new("delete tag", operations))?;
         Ok(())
     }))
 }
 
 pub fn new_project() -> Prompt {
     Prompt::Input(InputPrompt::new("New Project", 20, false, |app, name| {
-        let project = app.storage.create_project(name)?;
-        app.repository.add_project(project);
+        check_project_name(app, &name, None)?;
+        let transaction = app.storage.transaction()?;
+        let project = transaction.create_project(name)?;
+        Change::new("create project", vec![Operation::InsertProject(project)])
+            .record(transaction, &mut app.repository)?;
         app.sync();
         Ok(())
     }))
 }
 
-pub fn delete_project(project_id: ProjectId) -> Prompt {
+pub fn rename_project(project_id: ProjectId) -> Prompt {
+    Prompt::Input(InputPrompt::new(
+        "Rename Project",
+        20,
+        false,
+        move |app, name| {
+            check_project_name(app, &name, Some(project_id))?;
+            let from = Project::from_id(&project_id, &app.repository).name.clone();
+            app.execute(Change::new(
+                "rename project",
+                vec![Operation::RenameProject {
+                    id: project_id,
+                    from,
+                    to: name,
+                }],
+            ))?;
+            Ok(())
+        },
+    ))
+}
+
+pub fn delete_project(app: &App, project_id: ProjectId) -> Prompt {
+    let tasks = &Project::from_id(&project_id, &app.repository).tasks;
+    let action = format!("deleting selected project ({})", tasks_summary(app, tasks));
+    Prompt::Confirm(ConfirmPrompt::new(action, move |app| {
+        let selected = app.state.explorer.projects.selected;
+        let operations = history::delete_project(&app.repository, &project_id);
+        app.execute(Change::new("delete project", operations))?;
+        // Select the project above the deleted one
+        app.state.explorer.projects.selected = selected.saturating_sub(1);
+        app.state.explorer.project_changed(&app.repository);
+        Ok(())
+    }))
+}
+
+pub fn add_default_tag(project_id: ProjectId) -> Prompt {
+    let prompt =
+        SelectPrompt::<Tag>::new_multiple("Add default tags to project", move |app, tags| {
+            let description = describe("add", tags.len(), "default tag", "default tags");
+            let operations = tags
+                .into_iter()
+                .map(|tag_id| Operation::AddDefaultTag { project_id, tag_id })
+                .collect();
+            app.execute(Change::new(description, operations))
+        })
+        .filter(move |repository, tag| {
+            !Project::from_id(&project_id, repository)
+                .default_tags
+                .contains(&tag.id)
+        });
+    Prompt::TagSelect(prompt)
+}
+
+pub fn remove_default_tag(project_id: ProjectId) -> Prompt {
+    let prompt =
+        SelectPrompt::<Tag>::new_multiple("Remove default tags from project", move |app, tags| {
+            let description = describe("remove", tags.len(), "default tag", "default tags");
+            let operations = tags
+                .into_iter()
+                .map(|tag_id| Operation::RemoveDefaultTag { project_id, tag_id })
+                .collect();
+            app.execute(Change::new(description, operations))
+        })
+        .filter(move |repository, tag| {
+            Project::from_id(&project_id, repository)
+                .default_tags
+                .contains(&tag.id)
+        });
+    Prompt::TagSelect(prompt)
+}
+
+pub fn apply_default_tags(project_id: ProjectId) -> Prompt {
     Prompt::Confirm(ConfirmPrompt::new(
-        "deleting selected project",
+        "re-applying default tags to existing tasks",
         move |app| {
-            if app.state.explorer.projects.selected > 0 {
-                app.state.explorer.projects.selected -= 1;
-            }
-            app.storage.delete_project(&project_id)?;
-            app.repository.remove_project(&project_id);
-            app.sync();
-            Ok(())
+            let operations = history::apply_default_tags(&app.repository, &project_id);
+            app.execute(Change::new("apply default tags", operations))
         },
     ))
 }
@@ -49,19 +222,273 @@ pub fn new_task(project_id: ProjectId) -> Prompt {
         150,
         false,
         move |app, name| {
-            let task = app.storage.create_task(&project_id, name)?;
-            app.repository.add_task(task);
+            let transaction = app.storage.transaction()?;
+            let task = history::create_task(&app.repository, &transaction, &project_id, name)?;
+            Change::new("create task", vec![Operation::InsertTask(task)])
+                .record(transaction, &mut app.repository)?;
             app.sync();
             Ok(())
         },
     ))
 }
 
-pub fn delete_task(task_id: TaskId) -> Prompt {
-    Prompt::Confirm(ConfirmPrompt::new("deleting selected task", move |app| {
-        app.storage.delete_task(&task_id)?;
-        app.repository.remove_task(&task_id);
+pub fn rename_task(task_id: TaskId) -> Prompt {
+    Prompt::Input(InputPrompt::new(
+        "Rename Task",
+        150,
+        false,
+        move |app, name| {
+            let from = Task::from_id(&task_id, &app.repository).name.clone();
+            app.execute(Change::new(
+                "rename task",
+                vec![Operation::RenameTask {
+                    id: task_id,
+                    from,
+                    to: name,
+                }],
+            ))?;
+            Ok(())
+        },
+    ))
+}
+
+/// Empty notes clear the previous ones.
+pub fn edit_task_notes(task_id: TaskId) -> Prompt {
+    let prompt = InputPrompt::new("Task Notes", 500, false, move |app, notes| {
+        let from = Task::from_id(&task_id, &app.repository).notes.clone();
+        if notes == from {
+            return Ok(());
+        }
+        app.execute(Change::new(
+            "edit task notes",
+            vec![Operation::EditTaskNotes {
+                id: task_id,
+                from,
+                to: notes,
+            }],
+        ))
+    })
+    .allow_empty();
+    Prompt::Input(prompt)
+}
+
+pub fn move_task(task_id: TaskId) -> Prompt {
+    let prompt = SelectPrompt::<Project>::new("Move task to project", move |app, project_id| {
+        let previous = Task::from_id(&task_id, &app.repository).project_id;
+        app.execute(Change::new(
+            "move task",
+            vec![Operation::MoveTask {
+                id: task_id,
+                from: previous,
+                to: project_id,
+            }],
+        ))?;
+
+        let (removed, added) = app
+            .repository
+            .default_tags_swap(&task_id, &previous, &project_id);
+        if !removed.is_empty() || !added.is_empty() {
+            app.show_prompt(swap_default_tags(task_id, previous, project_id));
+        }
+        Ok(())
+    })
+    .filter(move |repository, project| {
+        !project.archived && project.id != Task::from_id(&task_id, repository).project_id
+    });
+    Prompt::ProjectSelect(prompt)
+}
+
+pub fn swap_default_tags(task_id: TaskId, from: ProjectId, to: ProjectId) -> Prompt {
+    Prompt::Confirm(ConfirmPrompt::new(
+        "replacing default tags of the previous project",
+        move |app| {
+            let operations = history::swap_default_tags(&app.repository, &task_id, &from, &to);
+            app.execute(Change::new("replace default tags", operations))
+        },
+    ))
+}
+
+/// Finds a task by its name, project or tags and selects it in the explorer.
+pub fn search() -> Prompt {
+    Prompt::TaskSelect(SelectPrompt::<Task>::new("Search", |app, task_id| {
+        app.state.explorer.select_task(&app.repository, &task_id);
+        app.state.explorer.collapsed = true;
+        app.update_focus();
+        Ok(())
+    }))
+}
+
+pub fn start_timer() -> Prompt {
+    let prompt = SelectPrompt::<Task>::new("Start timer on task", |app, task_id| {
+        app.start_timer(task_id)
+    })
+    .filter(|repository, task| {
+        !task.archived && !Project::from_id(&task.project_id, repository).archived
+    });
+    Prompt::TaskSelect(prompt)
+}
+
+/// Adds an entry covering a gap of the timeline.
+pub fn fill_gap(start: DateTime<Local>, end: DateTime<Local>) -> Prompt {
+    let prompt = SelectPrompt::<Task>::new("Add entry in gap to task", move |app, task_id| {
+        let transaction = app.storage.transaction()?;
+        let entry = transaction.create_entry(&task_id, start, Some(end))?;
+        Change::new("add entry", vec![Operation::InsertEntry(entry)])
+            .record(transaction, &mut app.repository)?;
         app.sync();
         Ok(())
+    })
+    .filter(|repository, task| {
+        !task.archived && !Project::from_id(&task.project_id, repository).archived
+    });
+    Prompt::TaskSelect(prompt)
+}
+
+pub fn delete_task(app: &App, task_id: TaskId) -> Prompt {
+    let entries = app.repository.task_entries(&task_id).len();
+    let time = app.repository.task_time(&task_id, Local::now());
+    let action = format!(
+        "deleting selected task ({}, {})",
+        count(entries, "entry", "entries"),
+        format_duration(&app.settings, time)
+    );
+    Prompt::Confirm(ConfirmPrompt::new(action, move |app| {
+        let operations = history::delete_task(&app.repository, &task_id);
+        app.execute(Change::new("delete task", operations))?;
+        Ok(())
+    }))
+}
+
+pub fn add_task_tag(task_id: TaskId) -> Prompt {
+    let prompt = SelectPrompt::<Tag>::new_multiple("Add tags to task", move |app, tags| {
+        let description = describe("add", tags.len(), "tag", "tags");
+        let operations = tags
+            .into_iter()
+            .map(|tag_id| Operation::AddTaskTag { task_id, tag_id })
+            .collect();
+        app.execute(Change::new(description, operations))
+    })
+    .filter(move |repository, tag| !Task::from_id(&task_id, repository).tags.contains(&tag.id));
+    Prompt::TagSelect(prompt)
+}
+
+pub fn remove_task_tag(task_id: TaskId) -> Prompt {
+    let prompt = SelectPrompt::<Tag>::new_multiple("Remove tags from task", move |app, tags| {
+        let description = describe("remove", tags.len(), "tag", "tags");
+        let operations = tags
+            .into_iter()
+            .map(|tag_id| Operation::RemoveTaskTag { task_id, tag_id })
+            .collect();
+        app.execute(Change::new(description, operations))
+    })
+    .filter(move |repository, tag| Task::from_id(&task_id, repository).tags.contains(&tag.id));
+    Prompt::TagSelect(prompt)
+}
+
+/// Fails if an entry would end before it starts, end in the future, or run
+/// from the future.
+fn check_interval(start: DateTime<Local>, end: Option<DateTime<Local>>) -> anyhow::Result<()> {
+    match end {
+        Some(end) if end <= start => bail!("The end must be after the start"),
+        Some(end) if end > Local::now() => bail!("A past entry cannot end in the future"),
+        None if start > Local::now() => bail!("A running entry cannot start in the future"),
+        _ => Ok(()),
+    }
+}
+
+/// Asks for the start then the end of a past entry.
+pub fn add_entry(task_id: TaskId) -> Prompt {
+    Prompt::DateTime(DateTimePrompt::new(
+        "Start of new entry",
+        move |app, start| {
+            let prompt = add_entry_end(task_id, start).suggest(time::format(start));
+            app.show_prompt(prompt);
+            Ok(())
+        },
+    ))
+}
+
+fn add_entry_end(task_id: TaskId, start: DateTime<Local>) -> Prompt {
+    Prompt::DateTime(DateTimePrompt::new("End of new entry", move |app, end| {
+        check_interval(start, Some(end))?;
+        let transaction = app.storage.transaction()?;
+        let entry = transaction.create_entry(&task_id, start, Some(end))?;
+        Change::new("add entry", vec![Operation::InsertEntry(entry)])
+            .record(transaction, &mut app.repository)?;
+        app.sync();
+        Ok(())
+    }))
+}
+
+fn update_entry(app: &mut App, description: &str, entry: Entry) -> anyhow::Result<()> {
+    check_interval(entry.start, entry.end)?;
+    let from = Entry::from_id(&entry.id, &app.repository).clone();
+    app.execute(Change::new(
+        description,
+        vec![Operation::UpdateEntry { from, to: entry }],
+    ))?;
+    Ok(())
+}
+
+pub fn edit_entry_start(entry_id: EntryId) -> Prompt {
+    Prompt::DateTime(DateTimePrompt::new("Start of entry", move |app, start| {
+        let entry = Entry::from_id(&entry_id, &app.repository).clone();
+        update_entry(app, "edit entry start", Entry { start, ..entry })
+    }))
+}
+
+/// Also stops the entry if it is running.
+pub fn edit_entry_end(entry_id: EntryId) -> Prompt {
+    Prompt::DateTime(DateTimePrompt::new("End of entry", move |app, end| {
+        let entry = Entry::from_id(&entry_id, &app.repository).clone();
+        update_entry(
+            app,
+            "edit entry end",
+            Entry {
+                end: Some(end),
+                ..entry
+            },
+        )
+    }))
+}
+
+/// Ends the entry at the chosen time and continues it in a new entry.
+pub fn split_entry(entry_id: EntryId) -> Prompt {
+    Prompt::DateTime(DateTimePrompt::new("Split entry at", move |app, at| {
+        let entry = Entry::from_id(&entry_id, &app.repository).clone();
+        if at <= entry.start || at >= entry.end.unwrap_or_else(Local::now) {
+            bail!("The entry must be split between its start and its end");
+        }
+        let first = Entry {
+            end: Some(at),
+            ..entry.clone()
+        };
+        let transaction = app.storage.transaction()?;
+        transaction.update_entry(&entry, &first)?;
+        let second = transaction.create_entry(&entry.task_id, at, entry.end)?;
+        Change::new(
+            "split entry",
+            vec![
+                Operation::UpdateEntry {
+                    from: entry,
+                    to: first,
+                },
+                Operation::InsertEntry(second),
+            ],
+        )
+        .record(transaction, &mut app.repository)?;
+        app.sync();
+        Ok(())
+    }))
+}
+
+pub fn delete_entry(entry_id: EntryId) -> Prompt {
+    Prompt::Confirm(ConfirmPrompt::new("deleting selected entry", move |app| {
+        let entry = Entry::from_id(&entry_id, &app.repository).clone();
+        app.execute(Change::new(
+            "delete entry",
+            vec![Operation::DeleteEntry(entry)],
+        ))?;
+        Ok(())
     }))
 }
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, TimeZone, Weekday};
+use clap::ValueEnum;
+
+use crate::app::Repository;
+use crate::model::*;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Grouping {
+    #[default]
+    Project,
+    Task,
+    Tag,
+}
+
+impl Grouping {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Task => "task",
+            Self::Tag => "tag",
+        }
+    }
+
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Project => Self::Task,
+            Self::Task => Self::Tag,
+            Self::Tag => Self::Project,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Period {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    pub fn cycle(self) -> Self {
+        match self {
+            Self::Day => Self::Week,
+            Self::Week => Self::Month,
+            Self::Month => Self::Day,
+        }
+    }
+
+    /// First day and the day after the last of the period containing `day`,
+    /// moved by `offset` periods.
+    pub fn bounds(
+        self,
+        day: NaiveDate,
+        offset: i32,
+        week_start: Weekday,
+    ) -> (NaiveDate, NaiveDate) {
+        match self {
+            Self::Day => {
+                let start = day + Duration::days(offset.into());
+                (start, start + Duration::days(1))
+            }
+            Self::Week => {
+                let start = day.week(week_start).first_day() + Duration::weeks(offset.into());
+                (start, start + Duration::weeks(1))
+            }
+            Self::Month => {
+                let first = day.with_day(1).expect("First day of month is valid");
+                let months = Months::new(offset.unsigned_abs());
+                let start = if offset < 0 {
+                    first - months
+                } else {
+                    first + months
+                };
+                (start, start + Months::new(1))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub grouping: Grouping,
+    pub total: Duration,
+    /// Sorted from the most time spent.
+    pub groups: Vec<Group>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub name: String,
+    pub duration: Duration,
+}
+
+impl Report {
+    pub fn new(start: DateTime<Local>, end: DateTime<Local>, grouping: Grouping) -> Self {
+        Self {
+            start,
+            end,
+            grouping,
+            total: Duration::zero(),
+            groups: Vec::new(),
+        }
+    }
+
+    /// Counts the time spent on an entry within the report, the groups must
+    /// be sorted again afterwards.
+    pub fn add(&mut self, repository: &Repository, entry: &Entry, now: DateTime<Local>) {
+        let duration = overlap(entry, self.start, self.end, now);
+        if duration.is_zero() {
+            return;
+        }
+        self.total += duration;
+
+        let task = Task::from_id(&entry.task_id, repository);
+        let project = Project::from_id(&task.project_id, repository);
+        let names = match self.grouping {
+            Grouping::Project => vec![project.name.clone()],
+            Grouping::Task => vec![format!("{}/{}", project.name, task.name)],
+            Grouping::Tag => tag_names(repository, task),
+        };
+        for name in names {
+            match self.groups.iter_mut().find(|group| group.name == name) {
+                Some(group) => group.duration += duration,
+                None => self.groups.push(Group { name, duration }),
+            }
+        }
+    }
+
+    /// Sorts groups from the most time spent.
+    pub fn sort(&mut self) {
+        self.groups
+            .sort_by(|a, b| b.duration.cmp(&a.duration).then(a.name.cmp(&b.name)));
+    }
+
+    /// Share of the total time spent in a group, between 0 and 1.
+    ///
+    /// When grouping by tag, entries count for each tag of their task so the
+    /// shares may add up to more than 1.
+    pub fn share(&self, group: &Group) -> f64 {
+        if self.total.is_zero() {
+            0.0
+        } else {
+            group.duration.num_seconds() as f64 / self.total.num_seconds() as f64
+        }
+    }
+}
+
+/// Names of the tags of a task, or `(untagged)`.
+fn tag_names(repository: &Repository, task: &Task) -> Vec<String> {
+    if task.tags.is_empty() {
+        return vec!["(untagged)".to_string()];
+    }
+    task.tags
+        .iter()
+        .map(|id| Tag::from_id(id, repository).name.clone())
+        .collect()
+}
+
+pub fn start_of_day(day: NaiveDate) -> DateTime<Local> {
+    let midnight = day.and_hms_opt(0, 0, 0).expect("Midnight is a valid time");
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&midnight))
+}
+
+/// Entries overlapping a day, from the earliest.
+pub fn day_entries(repository: &Repository, day: NaiveDate) -> Vec<&Entry> {
+    let start = start_of_day(day);
+    let end = start_of_day(day + Duration::days(1));
+    let mut entries: Vec<&Entry> = repository
+        .entries
+        .values()
+        .filter(|entry| entry.start < end && entry.end.is_none_or(|entry_end| entry_end > start))
+        .collect();
+    entries.sort_by_key(|entry| entry.start);
+    entries
+}
+
+/// Time spent within an interval, counting running entries up to `now`.
+pub fn overlap(
+    entry: &Entry,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    now: DateTime<Local>,
+) -> Duration {
+    let overlap = entry.end.unwrap_or(now).min(end) - entry.start.max(start);
+    overlap.max(Duration::zero())
+}
+
+/// Time spent per day by the finished entries on each key, a project or a tag,
+/// computed again when the repository changes rather than on every frame.
+#[derive(Debug)]
+pub struct DailyTotals<K> {
+    days: HashMap<NaiveDate, HashMap<K, Duration>>,
+    /// Counted up to the time of each frame instead.
+    running: Option<(Vec<K>, Entry)>,
+}
+
+impl<K> Default for DailyTotals<K> {
+    fn default() -> Self {
+        Self {
+            days: HashMap::new(),
+            running: None,
+        }
+    }
+}
+
+impl DailyTotals<ProjectId> {
+    pub fn projects(repository: &Repository) -> Self {
+        Self::new(repository, |task| vec![task.project_id])
+    }
+}
+
+impl DailyTotals<String> {
+    /// Entries count for each tag of their task, like in tag reports.
+    pub fn tags(repository: &Repository) -> Self {
+        Self::new(repository, |task| tag_names(repository, task))
+    }
+}
+
+impl<K: Clone + Eq + Hash> DailyTotals<K> {
+    fn new<F>(repository: &Repository, keys: F) -> Self
+    where
+        F: Fn(&Task) -> Vec<K>,
+    {
+        let mut totals = Self::default();
+        for entry in repository.entries.values() {
+            let keys = keys(Task::from_id(&entry.task_id, repository));
+            let end = match entry.end {
+                Some(end) => end,
+                None => {
+                    totals.running = Some((keys, entry.clone()));
+                    continue;
+                }
+            };
+            let mut day = entry.start.date_naive();
+            while start_of_day(day) < end {
+                let next = day + Duration::days(1);
+                let duration = overlap(entry, start_of_day(day), start_of_day(next), end);
+                let day_totals = totals.days.entry(day).or_default();
+                for key in &keys {
+                    *day_totals.entry(key.clone()).or_insert_with(Duration::zero) += duration;
+                }
+                day = next;
+            }
+        }
+        totals
+    }
+
+    /// Time spent on each of `days` days from `first`, on a key or on all of
+    /// them.
+    pub fn daily(
+        &self,
+        first: NaiveDate,
+        days: usize,
+        key: Option<&K>,
+        now: DateTime<Local>,
+    ) -> Vec<Duration> {
+        let mut totals: Vec<Duration> = (0..days)
+            .map(|day| {
+                let keys = match self.days.get(&(first + Duration::days(day as i64))) {
+                    Some(keys) => keys,
+                    None => return Duration::zero(),
+                };
+                match key {
+                    Some(key) => keys.get(key).copied().unwrap_or_else(Duration::zero),
+                    None => keys
+                        .values()
+                        .fold(Duration::zero(), |total, duration| total + *duration),
+                }
+            })
+            .collect();
+        if let Some((keys, entry)) = &self.running {
+            if key.is_none_or(|key| keys.contains(key)) {
+                for (day, total) in totals.iter_mut().enumerate() {
+                    let start = start_of_day(first + Duration::days(day as i64));
+                    let end = start_of_day(first + Duration::days(day as i64 + 1));
+                    *total += overlap(entry, start, end, now);
+                }
+            }
+        }
+        totals
+    }
+
+    /// Time spent on each key during `days` days from `first`, leaving out
+    /// the keys without any.
+    pub fn totals(
+        &self,
+        first: NaiveDate,
+        days: usize,
+        now: DateTime<Local>,
+    ) -> Vec<(K, Duration)> {
+        let mut totals: HashMap<K, Duration> = HashMap::new();
+        for day in 0..days {
+            if let Some(keys) = self.days.get(&(first + Duration::days(day as i64))) {
+                for (key, duration) in keys {
+                    *totals.entry(key.clone()).or_insert_with(Duration::zero) += *duration;
+                }
+            }
+        }
+        if let Some((keys, entry)) = &self.running {
+            let start = start_of_day(first);
+            let end = start_of_day(first + Duration::days(days as i64));
+            let duration = overlap(entry, start, end, now);
+            if !duration.is_zero() {
+                for key in keys {
+                    *totals.entry(key.clone()).or_insert_with(Duration::zero) += duration;
+                }
+            }
+        }
+        totals.into_iter().collect()
+    }
+}
+
+pub fn aggregate(
+    repository: &Repository,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    grouping: Grouping,
+    now: DateTime<Local>,
+) -> Report {
+    let mut report = Report::new(start, end, grouping);
+    for entry in repository.entries.values() {
+        report.add(repository, entry, now);
+    }
+    report.sort();
+    report
+}
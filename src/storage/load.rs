@@ -1,4 +1,4 @@
-use super::Storage;
+use super::{from_timestamp, Storage};
 use crate::app::Repository;
 use crate::model::*;
 
@@ -40,14 +40,15 @@ pub fn load_repository(storage: &Storage) -> anyhow::Result<Repository> {
     fill_map!(repository.tags(tags));
 
     let mut statement = storage.connection.prepare(
-        "SELECT p.id, p.name, GROUP_CONCAT(DISTINCT Tag.tag_id), GROUP_CONCAT(DISTINCT Task.id) FROM Project p LEFT JOIN DefaultTags Tag ON p.id = Tag.project_id LEFT JOIN Task ON p.id = Task.project_id GROUP BY p.id")?;
+        "SELECT p.id, p.name, p.archived, GROUP_CONCAT(DISTINCT Tag.tag_id), GROUP_CONCAT(DISTINCT Task.id) FROM Project p LEFT JOIN DefaultTags Tag ON p.id = Tag.project_id LEFT JOIN Task ON p.id = Task.project_id GROUP BY p.id")?;
     let projects: Vec<Project> = statement
         .query_map([], |row| {
             Ok(Project {
                 id: ProjectId(row.get(0)?),
                 name: row.get(1)?,
-                default_tags: parse_concat_row!(row[2], TagId),
-                tasks: parse_concat_row!(row[3], TaskId),
+                default_tags: parse_concat_row!(row[3], TagId),
+                tasks: parse_concat_row!(row[4], TaskId),
+                archived: row.get(2)?,
             })
         })?
         .map(|project| project.unwrap())
@@ -55,19 +56,40 @@ pub fn load_repository(storage: &Storage) -> anyhow::Result<Repository> {
     fill_map!(repository.projects(projects));
 
     let mut statement = storage.connection.prepare(
-        "SELECT id, project_id, name, GROUP_CONCAT(t.tag_id) FROM Task k LEFT JOIN TaskTags t ON k.id = t.task_id GROUP BY id")?;
+        "SELECT id, project_id, name, created_at, archived, notes, GROUP_CONCAT(t.tag_id) FROM Task k LEFT JOIN TaskTags t ON k.id = t.task_id GROUP BY id")?;
     let tasks: Vec<Task> = statement
         .query_map([], |row| {
             Ok(Task {
                 id: TaskId(row.get(0)?),
                 project_id: ProjectId(row.get(1)?),
                 name: row.get(2)?,
-                tags: parse_concat_row!(row[3], TagId),
+                tags: parse_concat_row!(row[6], TagId),
+                created_at: row.get::<usize, Option<i64>>(3)?.map(from_timestamp),
+                archived: row.get(4)?,
+                notes: row.get(5)?,
             })
         })?
         .map(|task| task.unwrap())
         .collect();
     fill_map!(repository.tasks(tasks));
 
+    let mut statement = storage
+        .connection
+        .prepare("SELECT id, task_id, start_time, end_time FROM Entry ORDER BY start_time")?;
+    let entries: Vec<Entry> = statement
+        .query_map([], |row| {
+            Ok(Entry {
+                id: EntryId(row.get(0)?),
+                task_id: TaskId(row.get(1)?),
+                start: from_timestamp(row.get(2)?),
+                end: row.get::<usize, Option<i64>>(3)?.map(from_timestamp),
+            })
+        })?
+        .map(|entry| entry.unwrap())
+        .collect();
+    for entry in entries {
+        repository.add_entry(entry);
+    }
+
     Ok(repository)
 }
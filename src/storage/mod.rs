@@ -1,5 +1,10 @@
-use rusqlite::{params, Connection};
+use std::path::Path;
 
+use anyhow::{bail, Context};
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::{params, Connection, OptionalExtension, Params};
+
+use crate::history::{Change, HISTORY_LIMIT};
 use crate::model::*;
 
 pub mod load;
@@ -9,14 +14,25 @@ mod embedded {
     embed_migrations!("./migrations");
 }
 
-pub fn init_storage() -> anyhow::Result<Storage> {
-    let mut connection = Connection::open("local.db")?;
+pub fn init_storage(path: &Path) -> anyhow::Result<Storage> {
+    let mut connection = Connection::open(path)
+        .with_context(|| format!("Could not open database {}", path.display()))?;
 
     embedded::migrations::runner().run(&mut connection).unwrap();
+    connection
+        .pragma_update(None, "foreign_keys", true)
+        .context("Could not enable foreign keys")?;
 
     Ok(Storage::new(connection))
 }
 
+fn from_timestamp(timestamp: i64) -> DateTime<Local> {
+    Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .expect("Invalid timestamp in storage")
+}
+
 pub struct Storage {
     connection: Connection,
 }
@@ -25,60 +41,378 @@ impl Storage {
     pub fn new(connection: Connection) -> Self {
         Self { connection }
     }
+
+    /// Starts the writes of a change, which are only kept once committed.
+    pub fn transaction(&self) -> anyhow::Result<Transaction<'_>> {
+        Ok(Transaction {
+            transaction: self.connection.unchecked_transaction()?,
+        })
+    }
 }
 
-impl Storage {
+/// Writes to the storage applied all at once, or not at all if dropped before
+/// being committed.
+///
+/// Writes which revert or replay a change fail when the rows they touch no
+/// longer hold the values recorded with the change.
+pub struct Transaction<'a> {
+    transaction: rusqlite::Transaction<'a>,
+}
+
+/// Fails unless a write touched the single row it expected to.
+fn check_changed(rows: usize, what: &str) -> anyhow::Result<()> {
+    if rows != 1 {
+        bail!("The {} was changed since", what);
+    }
+    Ok(())
+}
+
+impl Transaction<'_> {
+    pub fn commit(self) -> anyhow::Result<()> {
+        self.transaction.commit()?;
+        Ok(())
+    }
+
+    /// Whether the query returns any row.
+    fn exists<P: Params>(&self, query: &str, params: P) -> anyhow::Result<bool> {
+        Ok(self.transaction.prepare(query)?.exists(params)?)
+    }
+
     pub fn create_tag(&self, name: String) -> anyhow::Result<Tag> {
-        self.connection
+        self.transaction
             .execute("INSERT INTO Tag (name) VALUES (?)", params![name])?;
-        let id = self.connection.last_insert_rowid();
+        let id = self.transaction.last_insert_rowid();
         Ok(Tag {
             id: TagId::from(id),
             name,
         })
     }
 
-    pub fn delete_tag(&self, id: &TagId) -> anyhow::Result<()> {
-        self.connection
-            .execute("DELETE FROM Tag WHERE id = ?", params![id.0])?;
+    /// Inserts back a deleted tag with its original id.
+    pub fn restore_tag(&self, tag: &Tag) -> anyhow::Result<()> {
+        self.transaction.execute(
+            "INSERT INTO Tag (id, name) VALUES (?, ?)",
+            params![tag.id.0, tag.name],
+        )?;
         Ok(())
     }
 
+    pub fn rename_tag(&self, id: &TagId, from: &str, to: &str) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Tag SET name = ? WHERE id = ? AND name = ?",
+            params![to, id.0, from],
+        )?;
+        check_changed(rows, "tag")
+    }
+
+    /// Deletes a tag once it is no longer used by tasks or projects.
+    pub fn delete_tag(&self, tag: &Tag) -> anyhow::Result<()> {
+        let used = self.exists(
+            "SELECT 1 FROM TaskTags WHERE tag_id = ?1 UNION ALL SELECT 1 FROM DefaultTags WHERE tag_id = ?1",
+            params![tag.id.0],
+        )?;
+        if used {
+            bail!("The tag `{}` was changed since", tag.name);
+        }
+        let rows = self.transaction.execute(
+            "DELETE FROM Tag WHERE id = ? AND name = ?",
+            params![tag.id.0, tag.name],
+        )?;
+        check_changed(rows, "tag")
+    }
+
     pub fn create_project(&self, name: String) -> anyhow::Result<Project> {
-        self.connection
+        self.transaction
             .execute("INSERT INTO Project (name) VALUES (?)", params![name])?;
-        let id = self.connection.last_insert_rowid();
+        let id = self.transaction.last_insert_rowid();
         Ok(Project {
             id: ProjectId::from(id),
             name,
             default_tags: Vec::new(),
             tasks: Vec::new(),
+            archived: false,
         })
     }
 
-    pub fn delete_project(&self, id: &ProjectId) -> anyhow::Result<()> {
-        self.connection
-            .execute("DELETE FROM Project WHERE id = ?", params![id.0])?;
+    /// Inserts back a deleted project and its default tags.
+    pub fn restore_project(&self, project: &Project) -> anyhow::Result<()> {
+        self.transaction.execute(
+            "INSERT INTO Project (id, name, archived) VALUES (?, ?, ?)",
+            params![project.id.0, project.name, project.archived],
+        )?;
+        for tag_id in &project.default_tags {
+            self.add_default_tag(&project.id, tag_id)?;
+        }
         Ok(())
     }
 
+    pub fn rename_project(&self, id: &ProjectId, from: &str, to: &str) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Project SET name = ? WHERE id = ? AND name = ?",
+            params![to, id.0, from],
+        )?;
+        check_changed(rows, "project")
+    }
+
+    pub fn set_project_archived(&self, id: &ProjectId, archived: bool) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Project SET archived = ? WHERE id = ? AND archived = ?",
+            params![archived, id.0, !archived],
+        )?;
+        check_changed(rows, "project")
+    }
+
+    /// Deletes a project and its default tags once it has no tasks left.
+    pub fn delete_project(&self, project: &Project) -> anyhow::Result<()> {
+        for tag_id in &project.default_tags {
+            self.remove_default_tag(&project.id, tag_id)?;
+        }
+        let used = self.exists(
+            "SELECT 1 FROM Task WHERE project_id = ?1 UNION ALL SELECT 1 FROM DefaultTags WHERE project_id = ?1",
+            params![project.id.0],
+        )?;
+        if used {
+            bail!("The project `{}` was changed since", project.name);
+        }
+        let rows = self.transaction.execute(
+            "DELETE FROM Project WHERE id = ? AND name = ? AND archived = ?",
+            params![project.id.0, project.name, project.archived],
+        )?;
+        check_changed(rows, "project")
+    }
+
+    pub fn add_default_tag(&self, project_id: &ProjectId, tag_id: &TagId) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "INSERT OR IGNORE INTO DefaultTags (project_id, tag_id) VALUES (?, ?)",
+            params![project_id.0, tag_id.0],
+        )?;
+        check_changed(rows, "default tag")
+    }
+
+    pub fn remove_default_tag(&self, project_id: &ProjectId, tag_id: &TagId) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "DELETE FROM DefaultTags WHERE project_id = ? AND tag_id = ?",
+            params![project_id.0, tag_id.0],
+        )?;
+        check_changed(rows, "default tag")
+    }
+
     pub fn create_task(&self, project_id: &ProjectId, name: String) -> anyhow::Result<Task> {
-        self.connection.execute(
-            "INSERT INTO Task (project_id, name) VALUES (?, ?)",
-            params![project_id.0, name],
+        let created_at = Local::now();
+        self.transaction.execute(
+            "INSERT INTO Task (project_id, name, created_at) VALUES (?, ?, ?)",
+            params![project_id.0, name, created_at.timestamp()],
         )?;
-        let id = self.connection.last_insert_rowid();
+        let id = self.transaction.last_insert_rowid();
         Ok(Task {
             id: TaskId::from(id),
             project_id: *project_id,
             name,
             tags: Vec::new(),
+            created_at: Some(created_at),
+            archived: false,
+            notes: String::new(),
+        })
+    }
+
+    /// Inserts back a deleted task and its tags.
+    pub fn restore_task(&self, task: &Task) -> anyhow::Result<()> {
+        self.transaction.execute(
+            "INSERT INTO Task (id, project_id, name, created_at, archived, notes) VALUES (?, ?, ?, ?, ?, ?)",
+            params![
+                task.id.0,
+                task.project_id.0,
+                task.name,
+                task.created_at.map(|created_at| created_at.timestamp()),
+                task.archived,
+                task.notes
+            ],
+        )?;
+        for tag_id in &task.tags {
+            self.add_task_tag(&task.id, tag_id)?;
+        }
+        Ok(())
+    }
+
+    pub fn rename_task(&self, id: &TaskId, from: &str, to: &str) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Task SET name = ? WHERE id = ? AND name = ?",
+            params![to, id.0, from],
+        )?;
+        check_changed(rows, "task")
+    }
+
+    pub fn set_task_notes(&self, id: &TaskId, from: &str, to: &str) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Task SET notes = ? WHERE id = ? AND notes = ?",
+            params![to, id.0, from],
+        )?;
+        check_changed(rows, "task")
+    }
+
+    pub fn move_task(&self, id: &TaskId, from: &ProjectId, to: &ProjectId) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Task SET project_id = ? WHERE id = ? AND project_id = ?",
+            params![to.0, id.0, from.0],
+        )?;
+        check_changed(rows, "task")
+    }
+
+    pub fn set_task_archived(&self, id: &TaskId, archived: bool) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Task SET archived = ? WHERE id = ? AND archived = ?",
+            params![archived, id.0, !archived],
+        )?;
+        check_changed(rows, "task")
+    }
+
+    /// Deletes a task and its tags once it has no entries left.
+    pub fn delete_task(&self, task: &Task) -> anyhow::Result<()> {
+        for tag_id in &task.tags {
+            self.remove_task_tag(&task.id, tag_id)?;
+        }
+        let used = self.exists(
+            "SELECT 1 FROM Entry WHERE task_id = ?1 UNION ALL SELECT 1 FROM TaskTags WHERE task_id = ?1",
+            params![task.id.0],
+        )?;
+        if used {
+            bail!("The task `{}` was changed since", task.name);
+        }
+        let rows = self.transaction.execute(
+            "DELETE FROM Task WHERE id = ? AND project_id = ? AND name = ? AND archived = ? AND notes = ?",
+            params![task.id.0, task.project_id.0, task.name, task.archived, task.notes],
+        )?;
+        check_changed(rows, "task")
+    }
+
+    pub fn add_task_tag(&self, task_id: &TaskId, tag_id: &TagId) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "INSERT OR IGNORE INTO TaskTags (task_id, tag_id) VALUES (?, ?)",
+            params![task_id.0, tag_id.0],
+        )?;
+        check_changed(rows, "task tag")
+    }
+
+    pub fn remove_task_tag(&self, task_id: &TaskId, tag_id: &TagId) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "DELETE FROM TaskTags WHERE task_id = ? AND tag_id = ?",
+            params![task_id.0, tag_id.0],
+        )?;
+        check_changed(rows, "task tag")
+    }
+
+    pub fn create_entry(
+        &self,
+        task_id: &TaskId,
+        start: DateTime<Local>,
+        end: Option<DateTime<Local>>,
+    ) -> anyhow::Result<Entry> {
+        self.transaction.execute(
+            "INSERT INTO Entry (task_id, start_time, end_time) VALUES (?, ?, ?)",
+            params![task_id.0, start.timestamp(), end.map(|end| end.timestamp())],
+        )?;
+        let id = self.transaction.last_insert_rowid();
+        Ok(Entry {
+            id: EntryId::from(id),
+            task_id: *task_id,
+            start,
+            end,
         })
     }
 
-    pub fn delete_task(&self, id: &TaskId) -> anyhow::Result<()> {
-        self.connection
-            .execute("DELETE FROM Task WHERE id = ?", params![id.0])?;
+    /// Inserts back a deleted entry with its original id.
+    pub fn restore_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        self.transaction.execute(
+            "INSERT INTO Entry (id, task_id, start_time, end_time) VALUES (?, ?, ?, ?)",
+            params![
+                entry.id.0,
+                entry.task_id.0,
+                entry.start.timestamp(),
+                entry.end.map(|end| end.timestamp())
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn update_entry(&self, from: &Entry, to: &Entry) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "UPDATE Entry SET task_id = ?, start_time = ?, end_time = ?
+             WHERE id = ? AND task_id = ? AND start_time = ? AND end_time IS ?",
+            params![
+                to.task_id.0,
+                to.start.timestamp(),
+                to.end.map(|end| end.timestamp()),
+                from.id.0,
+                from.task_id.0,
+                from.start.timestamp(),
+                from.end.map(|end| end.timestamp())
+            ],
+        )?;
+        check_changed(rows, "entry")
+    }
+
+    pub fn delete_entry(&self, entry: &Entry) -> anyhow::Result<()> {
+        let rows = self.transaction.execute(
+            "DELETE FROM Entry WHERE id = ? AND task_id = ? AND start_time = ? AND end_time IS ?",
+            params![
+                entry.id.0,
+                entry.task_id.0,
+                entry.start.timestamp(),
+                entry.end.map(|end| end.timestamp())
+            ],
+        )?;
+        check_changed(rows, "entry")
+    }
+
+    /// Records a change to undo, dropping the changes which could be redone
+    /// and the oldest ones past [`HISTORY_LIMIT`].
+    pub fn push_change(&self, change: &Change) -> anyhow::Result<()> {
+        let operations = serde_json::to_string(&change.operations)?;
+        self.transaction
+            .execute("DELETE FROM History WHERE undone = 1", [])?;
+        self.transaction.execute(
+            "INSERT INTO History (description, operations) VALUES (?, ?)",
+            params![change.description, operations],
+        )?;
+        self.transaction.execute(
+            "DELETE FROM History WHERE id <= last_insert_rowid() - ?",
+            params![HISTORY_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    /// The last change which can be undone, or the first undone change to redo.
+    pub fn next_change(&self, undone: bool) -> anyhow::Result<Option<(i64, Change)>> {
+        let query = if undone {
+            "SELECT id, description, operations FROM History WHERE undone = 1 ORDER BY id LIMIT 1"
+        } else {
+            "SELECT id, description, operations FROM History WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+        };
+        let row = self
+            .transaction
+            .query_row(query, [], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                ))
+            })
+            .optional()?;
+        match row {
+            Some((id, description, operations)) => {
+                let operations =
+                    serde_json::from_str(&operations).context("Invalid change in history")?;
+                Ok(Some((id, Change::new(description, operations))))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_undone(&self, id: i64, undone: bool) -> anyhow::Result<()> {
+        self.transaction.execute(
+            "UPDATE History SET undone = ? WHERE id = ?",
+            params![undone, id],
+        )?;
         Ok(())
     }
 }
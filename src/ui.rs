@@ -1,18 +1,25 @@
+use chrono::{Duration, Local, NaiveDate};
 use lazy_static::lazy_static;
 
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Margin, Rect};
 use tui::style::{Color, Modifier, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use tui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap};
 use tui::Frame;
+use unicode_width::UnicodeWidthStr;
 
+pub mod calendar;
 pub mod explorer;
-mod util;
+pub mod reports;
+pub mod stats;
+pub mod timeline;
+pub mod util;
 
-use crate::app::App;
-use crate::input::Prompt;
-use crate::model::{Project, Tag, Task};
+use crate::app::{App, Pane, StatusKind};
+use crate::input::bindings::Action;
+use crate::input::{InputPrompt, Prompt, SelectPrompt, Selectable};
+use crate::model::{Entry, FromId, Project, Task};
 
 use self::explorer::Explorer;
 
@@ -21,15 +28,18 @@ lazy_static! {
         vertical: 0,
         horizontal: 1,
     };
-
-    // Bindings
-    static ref BINDINGS_PROMPT_TAG_SELECT: Vec<(&'static str, &'static str)> = [
-    ]
-    .into();
 }
 
 pub fn draw_frame<B: Backend>(f: &mut Frame<B>, app: &App) {
-    if app.state.explorer.collapsed {
+    if matches!(app.state.focus, Pane::Reports) {
+        reports::draw_reports(f, app, f.size());
+    } else if matches!(app.state.focus, Pane::Stats) {
+        stats::draw_stats(f, app, f.size());
+    } else if matches!(app.state.focus, Pane::Calendar) {
+        calendar::draw_calendar(f, app, f.size());
+    } else if matches!(app.state.focus, Pane::Timeline) {
+        timeline::draw_timeline(f, app, f.size());
+    } else if app.state.explorer.collapsed {
         draw_main(f, app, f.size());
     } else {
         let chunks = Layout::default()
@@ -41,15 +51,37 @@ pub fn draw_frame<B: Backend>(f: &mut Frame<B>, app: &App) {
     }
 
     draw_prompt(f, app);
+    draw_status(f, app);
 }
 
-pub fn draw_prompt_footer<'a, B, Bd>(f: &mut Frame<B>, app: &App, area: Option<Rect>, bindings: Bd)
-where
-    B: Backend,
-    Bd: Into<Vec<(&'a str, &'a str)>>,
-{
+/// Draws the status message over the last line, above prompts.
+fn draw_status<B: Backend>(f: &mut Frame<B>, app: &App) {
+    if let Some(status) = app.status() {
+        let size = f.size();
+        if size.height == 0 {
+            return;
+        }
+        let area = Rect::new(size.x, size.y + size.height - 1, size.width, 1);
+        let style = match status.kind {
+            StatusKind::Info => Style::default().add_modifier(Modifier::BOLD),
+            StatusKind::Error => Style::default()
+                .fg(app.settings.theme.error)
+                .add_modifier(Modifier::BOLD),
+        };
+        let message = Paragraph::new(Span::styled(format!(" {} ", status.message), style));
+        f.render_widget(Clear, area);
+        f.render_widget(message, area);
+    }
+}
+
+pub fn draw_prompt_footer<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    area: Option<Rect>,
+    actions: &[Action],
+) {
     if let Some(area) = area {
-        let bindings = util::bindings(bindings);
+        let bindings = util::bindings(&app.settings, app.bindings.hints(actions));
         let stack_length = app.state.prompt_stack.len();
         let stack_length = Paragraph::new(Span::styled(
             format!("({})", stack_length),
@@ -67,105 +99,63 @@ fn draw_prompt<B: Backend>(f: &mut Frame<B>, app: &App) {
     let area = f.size();
     if let Some(prompt) = app.prompt() {
         match prompt {
-            Prompt::Input(input) => {
-                let (area, clear, footer) = util::overlay(area, 3, true);
-                f.render_widget(Clear, clear);
-
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .title(input.title.as_ref());
-                let paragraph = Paragraph::new(input.value.as_ref()).block(block);
-                f.render_widget(paragraph, area);
-                f.set_cursor(area.x + 1 + input.value.len() as u16, area.y + 1);
-
-                let input_len = input.value.len();
-                let char_count = format!("{}/{}", input_len, input.limit);
-                if area.width as usize > input.title.len() + char_count.len() + 5 {
-                    let width = char_count.len() as u16;
-                    let mut char_count =
-                        Paragraph::new(char_count.as_ref()).alignment(Alignment::Right);
-                    if input_len >= input.limit {
-                        char_count = char_count.style(Style::default().fg(Color::Red));
-                    }
-                    let rect = Rect::new(area.x + area.width - 2 - width, area.y, width, 1);
-                    f.render_widget(char_count, rect);
-                }
-
-                draw_prompt_footer(f, app, footer, [("esc", "cancel"), ("enter", "continue")]);
-            }
-            Prompt::TagSelect(tag_select) => {
-                let (area, clear, footer) = util::overlay(area, 5 + area.height / 3, true);
-                f.render_widget(Clear, clear);
-
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Length(1),
-                        Constraint::Length(1),
-                        Constraint::Length(1),
-                        Constraint::Min(2),
-                        Constraint::Length(1),
-                    ])
-                    .split(area);
-
-                let block = Block::default()
-                    .borders(Borders::ALL)
-                    .title(tag_select.title.as_ref());
-                let search = "Search: ";
-                let cursor = (
-                    chunks[1].x + 1 + (search.len() + tag_select.search.len()) as u16,
-                    chunks[1].y,
-                );
-                let search = Paragraph::new(Spans::from(vec![
-                    Span::styled(search, Style::default().add_modifier(Modifier::BOLD)),
-                    Span::from(tag_select.search.as_ref()),
-                ]));
-
-                let divider = Paragraph::new(format!("├{}┤", "─".repeat(area.width as usize - 2)));
-
-                f.render_widget(block, area);
-                f.render_widget(search, chunks[1].inner(&MARGIN_BLOCK_H));
-                f.set_cursor(cursor.0, cursor.1);
-                f.render_widget(divider, chunks[2]);
-
-                let explorer = &tag_select.explorer;
-                explorer::draw_explorer(
-                    f,
-                    app,
-                    chunks[3].inner(&MARGIN_BLOCK_H),
-                    explorer,
-                    |tag: &Tag, selected| {
-                        let mut style = Style::default();
-                        if selected {
-                            style = style.add_modifier(Modifier::BOLD);
-                        }
-                        Spans::from(vec![
-                            Span::styled("* ", Style::default().add_modifier(Modifier::DIM)),
-                            Span::styled(&tag.name, style),
-                        ])
-                    },
-                    |p| p,
-                    false,
-                );
-                if let Some((position, area)) =
-                    util::list_position(area, explorer.selected + 1, explorer.items.len())
-                {
-                    f.render_widget(position, area);
-                }
-
-                draw_prompt_footer(
-                    f,
-                    app,
-                    footer,
-                    [
-                        ("esc", "cancel"),
-                        ("enter", "select"),
-                        ("ctrl+j", "down"),
-                        ("ctrl+k", "up"),
-                        ("ctrl+n", "create tag"),
-                    ],
-                );
+            Prompt::Input(input) => draw_input_prompt(f, app, input, None),
+            Prompt::DateTime(prompt) => {
+                let preview = match prompt.parse() {
+                    Ok(time) => Spans::from(vec![
+                        Span::styled("= ", Style::default().add_modifier(Modifier::DIM)),
+                        Span::styled(
+                            format!(
+                                "{} {}",
+                                util::format_day(&app.settings, time.date_naive()),
+                                util::format_time(&app.settings, time)
+                            ),
+                            Style::default().fg(app.settings.theme.accent),
+                        ),
+                    ]),
+                    Err(error) => Spans::from(Span::styled(
+                        error.to_string(),
+                        Style::default().fg(app.settings.theme.error),
+                    )),
+                };
+                draw_input_prompt(f, app, &prompt.input, Some(preview));
             }
+            Prompt::TagSelect(tag_select) => draw_select_prompt(
+                f,
+                app,
+                tag_select,
+                &[
+                    Action::PromptCancel,
+                    Action::PromptConfirm,
+                    Action::SelectMark,
+                    Action::SelectNewTag,
+                    Action::PromptHelp,
+                ],
+            ),
+            Prompt::ProjectSelect(project_select) => draw_select_prompt(
+                f,
+                app,
+                project_select,
+                &[
+                    Action::PromptCancel,
+                    Action::PromptConfirm,
+                    Action::SelectNext,
+                    Action::SelectPrevious,
+                    Action::PromptHelp,
+                ],
+            ),
+            Prompt::TaskSelect(task_select) => draw_select_prompt(
+                f,
+                app,
+                task_select,
+                &[
+                    Action::PromptCancel,
+                    Action::PromptConfirm,
+                    Action::SelectNext,
+                    Action::SelectPrevious,
+                    Action::PromptHelp,
+                ],
+            ),
             Prompt::Confirm(confirm) => {
                 let (area, clear, footer) = util::overlay(area, 3, true);
                 f.render_widget(Clear, clear);
@@ -184,23 +174,249 @@ fn draw_prompt<B: Backend>(f: &mut Frame<B>, app: &App) {
                 .block(block);
                 f.render_widget(paragraph, area);
 
-                draw_prompt_footer(f, app, footer, [("esc", "cancel"), ("enter", "continue")]);
+                draw_prompt_footer(
+                    f,
+                    app,
+                    footer,
+                    &[Action::PromptCancel, Action::PromptConfirm],
+                );
+            }
+            Prompt::Help(help) => {
+                let mut lines = Vec::new();
+                for context in &help.contexts {
+                    if !lines.is_empty() {
+                        lines.push(Spans::default());
+                    }
+                    lines.push(Spans::from(Span::styled(
+                        context.title(),
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )));
+                    for (keys, description) in app.bindings.list(*context) {
+                        lines.push(Spans::from(vec![
+                            Span::styled(
+                                format!("  {:<20}", keys),
+                                Style::default().fg(app.settings.theme.accent),
+                            ),
+                            Span::from(description),
+                        ]));
+                    }
+                }
+
+                let height = u16::min(lines.len() as u16 + 2, area.height.saturating_sub(4));
+                let (area, clear, footer) = util::overlay(area, height, true);
+                f.render_widget(Clear, clear);
+
+                let max_scroll = (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+                let block = Block::default().borders(Borders::ALL).title("Bindings");
+                let paragraph = Paragraph::new(lines)
+                    .block(block)
+                    .scroll((u16::min(help.scroll, max_scroll), 0));
+                f.render_widget(paragraph, area);
+
+                draw_prompt_footer(
+                    f,
+                    app,
+                    footer,
+                    &[Action::PromptCancel, Action::HelpDown, Action::HelpUp],
+                );
             }
         }
     }
 }
 
+/// Draws an input prompt, with a line under the value if there is a preview
+/// of what it stands for.
+fn draw_input_prompt<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    input: &InputPrompt,
+    preview: Option<Spans>,
+) {
+    let height = if preview.is_some() { 4 } else { 3 };
+    let (area, clear, footer) = util::overlay(f.size(), height, true);
+    f.render_widget(Clear, clear);
+    let area = match preview {
+        Some(preview) if area.height > 3 => {
+            let rect = Rect::new(area.x + 1, area.y + 3, area.width.saturating_sub(2), 1);
+            f.render_widget(Paragraph::new(preview), rect);
+            Rect::new(area.x, area.y, area.width, 3)
+        }
+        _ => area,
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(input.title.as_ref());
+    // Scrolls horizontally to keep the cursor visible
+    let cursor = input.cursor_width() as u16;
+    let scroll = (cursor + 1).saturating_sub(area.width.saturating_sub(2));
+    let paragraph = Paragraph::new(input.value.as_ref())
+        .block(block)
+        .scroll((0, scroll));
+    f.render_widget(paragraph, area);
+    f.set_cursor(area.x + 1 + cursor - scroll, area.y + 1);
+
+    let input_len = input.len();
+    let char_count = format!("{}/{}", input_len, input.limit);
+    if area.width as usize > input.title.len() + char_count.len() + 5 {
+        let width = char_count.len() as u16;
+        let mut char_count = Paragraph::new(char_count.as_ref()).alignment(Alignment::Right);
+        if input_len >= input.limit {
+            char_count = char_count.style(Style::default().fg(app.settings.theme.error));
+        }
+        let rect = Rect::new(area.x + area.width - 2 - width, area.y, width, 1);
+        f.render_widget(char_count, rect);
+    }
+
+    draw_prompt_footer(
+        f,
+        app,
+        footer,
+        &[
+            Action::PromptCancel,
+            Action::PromptConfirm,
+            Action::PromptHelp,
+        ],
+    );
+}
+
+fn draw_select_prompt<B, M>(
+    f: &mut Frame<B>,
+    app: &App,
+    select: &SelectPrompt<M>,
+    actions: &[Action],
+) where
+    B: Backend,
+    M: Selectable + FromId<M::Id>,
+{
+    let area = f.size();
+    let (area, clear, footer) = util::overlay(area, 5 + area.height / 3, true);
+    f.render_widget(Clear, clear);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(2),
+            Constraint::Length(1),
+        ])
+        .split(area);
+
+    let title = match select.marked.len() {
+        0 => select.title.clone(),
+        marked => format!("{} ({} marked)", select.title, marked),
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let search = "Search: ";
+    let cursor = (
+        chunks[1].x + 1 + (search.len() + select.search.width()) as u16,
+        chunks[1].y,
+    );
+    let search = Paragraph::new(Spans::from(vec![
+        Span::styled(search, Style::default().add_modifier(Modifier::BOLD)),
+        Span::from(select.search.as_ref()),
+    ]));
+
+    let divider = Paragraph::new(format!("├{}┤", "─".repeat(area.width as usize - 2)));
+
+    f.render_widget(block, area);
+    f.render_widget(search, chunks[1].inner(&MARGIN_BLOCK_H));
+    f.set_cursor(cursor.0, cursor.1);
+    f.render_widget(divider, chunks[2]);
+
+    let explorer = &select.explorer;
+    explorer::draw_explorer(
+        f,
+        app,
+        chunks[3].inner(&MARGIN_BLOCK_H),
+        explorer,
+        |item: &M, selected| {
+            let id = M::Id::from(item);
+            let mut style = Style::default();
+            if selected {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            let mut spans = if select.marked.contains(&id) {
+                vec![Span::styled(
+                    "+ ",
+                    Style::default()
+                        .fg(app.settings.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                )]
+            } else {
+                vec![Span::styled(
+                    "* ",
+                    Style::default().add_modifier(Modifier::DIM),
+                )]
+            };
+            spans.extend(util::highlight(
+                item.label(&app.repository),
+                select.matches.get(&id).map_or(&[], Vec::as_slice),
+                style,
+                style.fg(app.settings.theme.accent),
+            ));
+            Spans::from(spans)
+        },
+        |p| p,
+        false,
+    );
+    if let Some((position, area)) =
+        util::list_position(area, explorer.selected + 1, explorer.items.len())
+    {
+        f.render_widget(position, area);
+    }
+
+    draw_prompt_footer(f, app, footer, actions);
+}
+
 fn draw_project_explorer<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let explorer = &app.state.explorer.projects;
     let block = Block::default().borders(Borders::ALL).title("Projects");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    // Names, a space, then the sparklines when there is room for them
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(stats::sparklines_width(inner.width)),
+        ])
+        .split(inner);
     explorer::draw_explorer(
         f,
         app,
-        area,
-        &app.state.explorer.projects,
-        |project: &Project, selected| util::default_list_item(&project.name, selected),
-        move |p| p.block(block),
-        true,
+        chunks[0],
+        explorer,
+        |project: &Project, selected| {
+            let mut item = util::default_list_item(&project.name, selected);
+            if project.archived {
+                item.0[0].style = item.0[0].style.add_modifier(Modifier::DIM);
+            }
+            item
+        },
+        |p| p,
+        false,
     );
+    if chunks[2].width > 0 {
+        stats::draw_project_sparklines(
+            f,
+            &app.state.daily,
+            chunks[2],
+            &explorer.items,
+            Style::default()
+                .fg(app.settings.theme.accent)
+                .add_modifier(Modifier::DIM),
+        );
+    }
+    if let Some((position, rect)) =
+        util::list_position(area, explorer.selected + 1, explorer.items.len())
+    {
+        f.render_widget(position, rect);
+    }
 }
 
 fn draw_main<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
@@ -215,6 +431,13 @@ fn draw_main<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
             Span::styled(">> ", Style::default().add_modifier(Modifier::DIM)),
             Span::styled(&project.name, Style::default().add_modifier(Modifier::BOLD)),
         ];
+        if !project.default_tags.is_empty() {
+            let tags = util::tag_names(&app.repository, &project.default_tags);
+            breadcrumb.push(Span::styled(
+                format!(" [{}]", tags.join(", ")),
+                Style::default().add_modifier(Modifier::DIM),
+            ));
+        }
         if app.state.explorer.collapsed {
             if let Some(task) = app.state.explorer.tasks().selected::<Task>(&app.repository) {
                 breadcrumb.push(Span::styled(
@@ -228,6 +451,21 @@ fn draw_main<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
         let breadcrumb = Paragraph::new(breadcrumb);
         f.render_widget(breadcrumb, area);
 
+        // Running timer
+        if let Some(entry) = app.repository.running_entry() {
+            let task = Task::from_id(&entry.task_id, &app.repository);
+            let timer = Paragraph::new(Spans::from(vec![
+                Span::styled("● ", Style::default().fg(app.settings.theme.running)),
+                Span::from(task.name.as_ref()),
+                Span::styled(
+                    format!(" {}", util::format_clock(entry.duration(Local::now()))),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ]))
+            .alignment(Alignment::Right);
+            f.render_widget(timer, Rect::new(area.x, area.y, area.width, 1));
+        }
+
         // Project pane
         let area = Rect::new(area.x, area.y + 1, area.width, area.height - 1);
         draw_project_pane(f, app, area, project);
@@ -261,6 +499,9 @@ fn draw_project_pane<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, projec
             if app.state.explorer.collapsed && selected {
                 style = style.add_modifier(Modifier::BOLD);
             }
+            if task.archived {
+                style = style.add_modifier(Modifier::DIM);
+            }
             Spans::from(vec![
                 Span::styled("* ", Style::default().add_modifier(Modifier::DIM)),
                 Span::styled(&task.name, style),
@@ -297,34 +538,164 @@ fn draw_task_pane<B: Backend>(
         .constraints([
             Constraint::Length(4),
             Constraint::Length(3),
+            Constraint::Length(3),
             Constraint::Min(1),
         ])
         .split(area);
 
     // About
     let block = Block::default().borders(Borders::ALL).title("About");
+    let now = Local::now();
+    let mut time_spent = vec![
+        Span::from("Time spent: "),
+        Span::styled(
+            util::format_duration(&app.settings, app.repository.task_time(&task.id, now)),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+    ];
+    if let Some(entry) = app
+        .repository
+        .running_entry()
+        .filter(|entry| entry.task_id == task.id)
+    {
+        time_spent.push(Span::styled(
+            format!(" ● {}", util::format_clock(entry.duration(now))),
+            Style::default().fg(app.settings.theme.running),
+        ));
+    }
     let about = vec![
         Spans::from(vec![
             Span::from("Added on: "),
             Span::styled(
-                "19th Jan 2038",
+                task.created_at
+                    .map(|created_at| util::format_date(&app.settings, created_at))
+                    .unwrap_or_else(|| "Unknown".to_string()),
                 Style::default().add_modifier(Modifier::BOLD),
             ),
         ]),
-        Spans::from(vec![
-            Span::from("Time spent: "),
-            Span::styled("XXhXX", Style::default().add_modifier(Modifier::BOLD)),
-        ]),
+        Spans::from(time_spent),
     ];
     let about = Paragraph::new(about).block(block);
     f.render_widget(about, chunks[0]);
 
     // Tags
     let block = Block::default().borders(Borders::ALL).title("Tags");
-    let tags = Paragraph::new("None").block(block);
-    f.render_widget(tags, chunks[1]);
+    let tags = util::tag_names(&app.repository, &task.tags);
+    let tags = if tags.is_empty() {
+        Paragraph::new(Span::styled(
+            "None",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+    } else {
+        Paragraph::new(tags.join(", ")).wrap(Wrap { trim: true })
+    };
+    f.render_widget(tags.block(block), chunks[1]);
+
+    // Notes
+    let block = Block::default().borders(Borders::ALL).title("Notes");
+    let notes = if task.notes.is_empty() {
+        Paragraph::new(Span::styled(
+            "None",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+    } else {
+        Paragraph::new(task.notes.as_str()).wrap(Wrap { trim: true })
+    };
+    f.render_widget(notes.block(block), chunks[2]);
 
     // Time entries
+    draw_entries_table(f, app, chunks[3]);
+}
+
+fn draw_entries_table<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let now = Local::now();
+    let explorer = app.state.explorer.entries();
+    let focused = matches!(app.state.focus, Pane::Entries);
+
+    // Entries are sorted from most recent, group them by starting day
+    let mut days: Vec<(NaiveDate, Vec<(usize, &Entry)>)> = Vec::new();
+    for (idx, entry) in explorer
+        .items::<Entry>(&app.repository)
+        .into_iter()
+        .enumerate()
+    {
+        let day = entry.start.date_naive();
+        match days.last_mut() {
+            Some((last, entries)) if *last == day => entries.push((idx, entry)),
+            _ => days.push((day, vec![(idx, entry)])),
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut selected_row = None;
+    for (day, entries) in days {
+        let subtotal = entries.iter().fold(Duration::zero(), |total, (_, entry)| {
+            total + entry.duration(now)
+        });
+        rows.push(
+            Row::new(vec![
+                Cell::from(util::format_day(&app.settings, day)),
+                Cell::from(""),
+                Cell::from(util::format_duration(&app.settings, subtotal)),
+            ])
+            .style(Style::default().add_modifier(Modifier::DIM)),
+        );
+        for (idx, entry) in entries {
+            if idx == explorer.selected {
+                selected_row = Some(rows.len());
+            }
+            let end = match entry.end {
+                Some(end) => Cell::from(util::format_time(&app.settings, end)),
+                None => {
+                    Cell::from("running").style(Style::default().fg(app.settings.theme.running))
+                }
+            };
+            rows.push(Row::new(vec![
+                Cell::from(format!(
+                    "  {}",
+                    util::format_time(&app.settings, entry.start)
+                )),
+                end,
+                Cell::from(util::format_duration(&app.settings, entry.duration(now))),
+            ]));
+        }
+    }
+
     let block = Block::default().borders(Borders::ALL).title("Time");
-    f.render_widget(block, chunks[2]);
+    if rows.is_empty() {
+        let paragraph = Paragraph::new(Span::styled(
+            "No time recorded yet.",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+        .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let highlight = if focused {
+        Style::default().add_modifier(Modifier::REVERSED)
+    } else {
+        Style::default().add_modifier(Modifier::BOLD)
+    };
+    let header = Row::new(vec!["Start", "End", "Duration"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Min(16),
+        Constraint::Length(8),
+        Constraint::Length(8),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&widths)
+        .highlight_style(highlight);
+    let mut state = TableState::default();
+    state.select(selected_row);
+    f.render_stateful_widget(table, area, &mut state);
+
+    if let Some((position, area)) =
+        util::list_position(area, explorer.selected + 1, explorer.items.len())
+    {
+        f.render_widget(position, area);
+    }
 }
@@ -0,0 +1,201 @@
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use tui::Frame;
+
+use crate::app::App;
+use crate::model::{Entry, FromId, Project, Task};
+use crate::report;
+
+use super::util;
+
+/// Width of the weekday labels on the left of the heatmap.
+const LABEL_WIDTH: u16 = 4;
+/// Width of a day, a square and a space.
+const CELL_WIDTH: u16 = 2;
+const MAX_WEEKS: usize = 53;
+/// Colors of the days from the least to the most time spent.
+const SHADES: [Color; 4] = [
+    Color::Indexed(22),
+    Color::Indexed(28),
+    Color::Indexed(34),
+    Color::Indexed(40),
+];
+
+#[derive(Default, Debug)]
+pub struct CalendarState {
+    /// Selected day, today when unset.
+    selected: Option<NaiveDate>,
+    /// Whether the entries of the selected day are listed.
+    pub open: bool,
+}
+
+impl CalendarState {
+    pub fn selected(&self) -> NaiveDate {
+        self.selected.unwrap_or_else(|| Local::now().date_naive())
+    }
+
+    /// Moves the selection by some days, up to today.
+    pub fn shift(&mut self, days: i64) {
+        let today = Local::now().date_naive();
+        self.selected = Some((self.selected() + Duration::days(days)).min(today));
+    }
+
+    pub fn today(&mut self) {
+        self.selected = None;
+    }
+}
+
+fn shade(duration: Duration) -> Style {
+    let level = match duration.num_minutes() {
+        0 => return Style::default().fg(Color::DarkGray),
+        1..=59 => 0,
+        60..=179 => 1,
+        180..=359 => 2,
+        _ => 3,
+    };
+    Style::default().fg(SHADES[level])
+}
+
+pub fn draw_calendar<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let state = &app.state.calendar;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(10),
+            Constraint::Length(1),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    // Weeks fitting in the width, paging back from the current week
+    let weeks = usize::from(chunks[0].width.saturating_sub(2 + LABEL_WIDTH) / CELL_WIDTH)
+        .clamp(1, MAX_WEEKS);
+    let now = Local::now();
+    let week_start = app.settings.week_start;
+    let current_week = now.date_naive().week(week_start).first_day();
+    let selected = state.selected();
+    let selected_week = selected.week(week_start).first_day();
+    let page = (current_week - selected_week).num_weeks() as usize / weeks;
+    let first = current_week - Duration::weeks((page * weeks + weeks - 1) as i64);
+    let daily = app.state.daily.daily(first, weeks * 7, None, now);
+
+    let label_width = usize::from(LABEL_WIDTH);
+    let mut months = " ".repeat(label_width + weeks * usize::from(CELL_WIDTH));
+    for week in 0..weeks {
+        let day = first + Duration::weeks(week as i64);
+        let end = day + Duration::days(6);
+        if week == 0 || day.month() != end.month() || day.day() == 1 {
+            let month = end.format("%b").to_string();
+            let position = label_width + week * usize::from(CELL_WIDTH);
+            if position + month.len() <= months.len() && months[position..].starts_with("   ") {
+                months.replace_range(position..position + month.len(), &month);
+            }
+        }
+    }
+
+    let mut lines = vec![Spans::from(Span::styled(
+        months,
+        Style::default().add_modifier(Modifier::DIM),
+    ))];
+    for weekday in 0..7 {
+        let label = (first + Duration::days(weekday)).format("%a").to_string();
+        let mut spans = vec![Span::styled(
+            format!("{:<width$}", label, width = label_width),
+            Style::default().add_modifier(Modifier::DIM),
+        )];
+        for week in 0..weeks {
+            let index = week * 7 + weekday as usize;
+            let day = first + Duration::days(index as i64);
+            if day > now.date_naive() {
+                break;
+            }
+            let mut style = shade(daily[index]);
+            if day == selected {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            spans.push(Span::styled("■", style));
+            spans.push(Span::from(" "));
+        }
+        lines.push(Spans::from(spans));
+    }
+    let heatmap =
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+            "{} to {}",
+            util::format_date(&app.settings, report::start_of_day(first)),
+            util::format_date(
+                &app.settings,
+                report::start_of_day(first + Duration::days(weeks as i64 * 7 - 1))
+            )
+        )));
+    f.render_widget(heatmap, chunks[0]);
+
+    let entries = report::day_entries(&app.repository, selected);
+    let start = report::start_of_day(selected);
+    let end = report::start_of_day(selected + Duration::days(1));
+    let total = entries.iter().fold(Duration::zero(), |total, entry| {
+        total + report::overlap(entry, start, end, now)
+    });
+    let summary = Paragraph::new(Spans::from(vec![
+        Span::styled(">> ", Style::default().add_modifier(Modifier::DIM)),
+        Span::styled(
+            util::format_day(&app.settings, selected),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                ", {} in {}",
+                util::format_duration(&app.settings, total),
+                match entries.len() {
+                    1 => "1 entry".to_string(),
+                    count => format!("{} entries", count),
+                }
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ]));
+    f.render_widget(summary, chunks[1]);
+
+    if state.open && !entries.is_empty() {
+        draw_day_entries(f, app, chunks[2], &entries);
+    }
+}
+
+fn draw_day_entries<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, entries: &[&Entry]) {
+    let now = Local::now();
+    let rows: Vec<Row> = entries
+        .iter()
+        .map(|entry| {
+            let task = Task::from_id(&entry.task_id, &app.repository);
+            let project = Project::from_id(&task.project_id, &app.repository);
+            let end = match entry.end {
+                Some(end) => Cell::from(util::format_time(&app.settings, end)),
+                None => {
+                    Cell::from("running").style(Style::default().fg(app.settings.theme.running))
+                }
+            };
+            Row::new(vec![
+                Cell::from(util::format_time(&app.settings, entry.start)),
+                end,
+                Cell::from(util::format_duration(&app.settings, entry.duration(now))),
+                Cell::from(format!("{}/{}", project.name, task.name)),
+            ])
+        })
+        .collect();
+    let header = Row::new(vec!["Start", "End", "Duration", "Task"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Percentage(70),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Entries"))
+        .widths(&widths);
+    f.render_widget(table, area);
+}
@@ -5,19 +5,23 @@ use tui::widgets::Paragraph;
 use tui::Frame;
 
 use crate::app::{App, Repository};
-use crate::model::{FromId, Project, ProjectId, TaskId};
+use std::cmp::Reverse;
+
+use crate::model::{EntryId, FromId, Project, ProjectId, Task, TaskId};
 
 use super::util;
 
 pub trait Explorer<T> {
     fn previous(&mut self);
     fn next(&mut self);
+    fn first(&mut self);
+    fn last(&mut self);
 
     fn items<'a, I>(&'a self, repository: &'a Repository) -> Vec<&'a I>
     where
         I: FromId<T>;
 
-    fn selected_raw<'a>(&'a self) -> Option<&'a T>;
+    fn selected_raw(&self) -> Option<&T>;
     fn selected<'a, I>(&'a self, repository: &'a Repository) -> Option<&'a I>
     where
         I: FromId<T>;
@@ -33,7 +37,11 @@ pub struct ExplorerGroup<T> {
 pub struct ExplorerState {
     pub projects: ExplorerGroup<ProjectId>,
     pub collapsed: bool,
+
+    /// Whether archived projects and tasks are listed, after the others.
+    pub show_archived: bool,
     pub tasks: Option<ExplorerGroup<TaskId>>,
+    pub entries: Option<ExplorerGroup<EntryId>>,
 }
 
 impl<T> Default for ExplorerGroup<T> {
@@ -59,6 +67,14 @@ impl<T> Explorer<T> for ExplorerGroup<T> {
         }
     }
 
+    fn first(&mut self) {
+        self.selected = 0;
+    }
+
+    fn last(&mut self) {
+        self.selected = self.items.len().saturating_sub(1);
+    }
+
     fn items<'a, I>(&'a self, repository: &'a Repository) -> Vec<&'a I>
     where
         I: FromId<T>,
@@ -69,7 +85,7 @@ impl<T> Explorer<T> for ExplorerGroup<T> {
             .collect()
     }
 
-    fn selected_raw<'a>(&'a self) -> Option<&'a T> {
+    fn selected_raw(&self) -> Option<&T> {
         self.items.get(self.selected)
     }
 
@@ -84,6 +100,16 @@ impl<T> Explorer<T> for ExplorerGroup<T> {
 }
 
 impl<Id> ExplorerGroup<Id> {
+    /// Selects the given item if it is listed.
+    pub fn select(&mut self, id: &Id)
+    where
+        Id: PartialEq,
+    {
+        if let Some(index) = self.items.iter().position(|item| item.eq(id)) {
+            self.selected = index;
+        }
+    }
+
     pub fn sync_and_sort<'m, M, F, K>(&mut self, mut items: Vec<&'m M>, sort_key: F)
     where
         Id: From<&'m M> + Clone + PartialEq,
@@ -102,33 +128,71 @@ impl<Id> ExplorerGroup<Id> {
 
 impl ExplorerState {
     pub fn sync(&mut self, repository: &Repository) {
-        self.projects
-            .sync_and_sort(repository.projects.values().collect(), |item| {
-                item.name.clone()
-            });
+        let show_archived = self.show_archived;
+        self.projects.sync_and_sort(
+            repository
+                .projects
+                .values()
+                .filter(|project| show_archived || !project.archived)
+                .collect(),
+            |item| (item.archived, item.name.clone()),
+        );
         self.project_changed(repository);
     }
 
     pub fn project_changed(&mut self, repository: &Repository) {
         if let Some(project) = self.projects.selected::<Project>(repository) {
-            let mut tasks = ExplorerGroup::default();
+            let mut tasks = self.tasks.take().unwrap_or_default();
             tasks.sync_and_sort(
                 repository
                     .tasks
                     .values()
                     .filter(|task| project.tasks.contains(&task.id))
+                    .filter(|task| self.show_archived || !task.archived)
                     .collect(),
-                |item| item.name.clone(),
+                |item| (item.archived, item.name.clone()),
             );
             self.tasks = Some(tasks);
         } else {
             self.tasks = None;
         }
+        self.task_changed(repository);
+    }
+
+    pub fn task_changed(&mut self, repository: &Repository) {
+        if let Some(task_id) = self.tasks.as_ref().and_then(|tasks| tasks.selected_raw()) {
+            let mut entries = self.entries.take().unwrap_or_default();
+            entries.sync_and_sort(repository.task_entries(task_id), |item| Reverse(item.start));
+            self.entries = Some(entries);
+        } else {
+            self.entries = None;
+        }
+    }
+
+    /// Selects a task and its project, listing archived items if it is one.
+    pub fn select_task(&mut self, repository: &Repository, task_id: &TaskId) {
+        let task = Task::from_id(task_id, repository);
+        if task.archived || Project::from_id(&task.project_id, repository).archived {
+            self.show_archived = true;
+            self.sync(repository);
+        }
+        self.projects.select(&task.project_id);
+        self.project_changed(repository);
+        if let Some(tasks) = &mut self.tasks {
+            tasks.select(task_id);
+        }
+        self.task_changed(repository);
     }
 
     pub fn tasks(&self) -> &ExplorerGroup<TaskId> {
         self.tasks.as_ref().expect("Tasks explorer is not synced")
     }
+
+    pub fn entries(&self) -> &ExplorerGroup<EntryId> {
+        self.entries
+            .as_ref()
+            .expect("Entries explorer is not synced")
+    }
 }
 
 pub fn draw_explorer<B, E, T, Tf, Tp>(
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Local};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table};
+use tui::Frame;
+
+use crate::app::{App, Repository, Settings};
+use crate::report::{self, Grouping, Period, Report};
+
+use super::util;
+
+/// Width of the bar showing the share of each group.
+const SHARE_WIDTH: usize = 20;
+
+#[derive(Default, Debug)]
+pub struct ReportState {
+    pub period: Period,
+    pub grouping: Grouping,
+    /// Number of periods before (negative) or after the current one.
+    pub offset: i32,
+
+    /// Report of the finished entries, only computed again when the
+    /// repository or the displayed period changes, or the reports are opened.
+    cache: Option<Report>,
+}
+
+impl ReportState {
+    /// Forgets the cached report, it is computed again on the next refresh.
+    pub fn invalidate(&mut self) {
+        self.cache = None;
+    }
+
+    pub fn refresh(&mut self, repository: &Repository, settings: &Settings) {
+        if self.cache.is_some() {
+            return;
+        }
+        let today = Local::now().date_naive();
+        let (start, end) = self.period.bounds(today, self.offset, settings.week_start);
+        let mut report = Report::new(
+            report::start_of_day(start),
+            report::start_of_day(end),
+            self.grouping,
+        );
+        let now = Local::now();
+        for entry in repository.entries.values() {
+            if !entry.is_running() {
+                report.add(repository, entry, now);
+            }
+        }
+        report.sort();
+        self.cache = Some(report);
+    }
+
+    /// The cached report completed with the running entry.
+    pub fn report(&self, repository: &Repository, now: DateTime<Local>) -> Option<Report> {
+        let mut report = self.cache.clone()?;
+        if let Some(entry) = repository.running_entry() {
+            report.add(repository, entry, now);
+            report.sort();
+        }
+        Some(report)
+    }
+}
+
+fn title(settings: &Settings, state: &ReportState, report: &Report) -> String {
+    match state.period {
+        Period::Day => util::format_day(settings, report.start.date_naive()),
+        Period::Week => format!("Week of {}", util::format_date(settings, report.start)),
+        Period::Month => report.start.format("%B %Y").to_string(),
+    }
+}
+
+pub fn draw_reports<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let state = &app.state.reports;
+    let report = match state.report(&app.repository, Local::now()) {
+        Some(report) => report,
+        None => return,
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1)])
+        .split(area);
+
+    let header = Paragraph::new(Spans::from(vec![
+        Span::styled(">> ", Style::default().add_modifier(Modifier::DIM)),
+        Span::styled(
+            title(&app.settings, state, &report),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                " by {}, {} in total",
+                report.grouping.name(),
+                util::format_duration(&app.settings, report.total)
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ]));
+    f.render_widget(header, chunks[0]);
+
+    let block = Block::default().borders(Borders::ALL).title("Report");
+    if report.groups.is_empty() {
+        let paragraph = Paragraph::new(Span::styled(
+            "No time recorded in this period.",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+        .block(block);
+        f.render_widget(paragraph, chunks[1]);
+        return;
+    }
+
+    let rows: Vec<Row> = report
+        .groups
+        .iter()
+        .map(|group| {
+            let share = report.share(group);
+            let bar = "█".repeat((share * SHARE_WIDTH as f64).round() as usize);
+            Row::new(vec![
+                Cell::from(group.name.clone()),
+                Cell::from(util::format_duration(&app.settings, group.duration)),
+                Cell::from(format!("{:>3.0}%", share * 100.0)),
+                Cell::from(bar).style(Style::default().fg(app.settings.theme.accent)),
+            ])
+        })
+        .collect();
+    let header = Row::new(vec!["Name", "Duration", "Share", ""])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Percentage(50),
+        Constraint::Length(8),
+        Constraint::Length(5),
+        Constraint::Length(SHARE_WIDTH as u16),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&widths)
+        .column_spacing(2);
+    f.render_widget(table, chunks[1]);
+}
@@ -0,0 +1,228 @@
+use chrono::{Duration, Local, NaiveDate};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{BarChart, Block, Borders, Paragraph, Sparkline};
+use tui::Frame;
+
+use crate::app::App;
+use crate::model::ProjectId;
+use crate::report::DailyTotals;
+
+use super::util;
+
+/// Bars of the day chart, wide enough for their value in hours such as `10.5`.
+const BAR_WIDTH: u16 = 4;
+const MAX_DAYS: usize = 31;
+/// Days shown by the sparklines of the project explorer.
+const SPARKLINE_DAYS: usize = 14;
+
+#[derive(Default, Debug)]
+pub struct StatsState {
+    /// Number of charts widths before (negative) or after the current days.
+    pub offset: i32,
+}
+
+fn minutes(duration: Duration) -> u64 {
+    duration.num_minutes().max(0) as u64
+}
+
+/// Writes the value of each bar in hours over the minutes of the chart, the
+/// bars being laid out in `area` as in a [`BarChart`] with a gap of 1.
+fn draw_hours<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    durations: &[Duration],
+    bar_width: u16,
+    style: Style,
+) {
+    if area.height < 2 {
+        return;
+    }
+    for (index, duration) in durations.iter().enumerate() {
+        let x = area.x + index as u16 * (bar_width + 1);
+        if x + bar_width > area.right() {
+            break;
+        }
+        let hours = format!("{:.1}", duration.num_minutes() as f64 / 60.0);
+        if minutes(*duration) == 0 || hours.len() > usize::from(bar_width) {
+            continue;
+        }
+        let value = Paragraph::new(Span::styled(
+            format!("{:^width$}", hours, width = usize::from(bar_width)),
+            style,
+        ));
+        f.render_widget(value, Rect::new(x, area.bottom() - 2, bar_width, 1));
+    }
+}
+
+/// Number of days fitting in the width of the chart.
+fn days_in(area: Rect) -> usize {
+    let inner = area.width.saturating_sub(2) + 1;
+    usize::from(inner / (BAR_WIDTH + 1)).clamp(1, MAX_DAYS)
+}
+
+pub fn draw_stats<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Percentage(55),
+            Constraint::Min(5),
+        ])
+        .split(area);
+
+    let now = Local::now();
+    let days = days_in(chunks[1]);
+    let last = now.date_naive() + Duration::days(i64::from(app.state.stats.offset) * days as i64);
+    let first = last - Duration::days(days as i64 - 1);
+    let daily = app.state.daily.daily(first, days, None, now);
+    let total = daily
+        .iter()
+        .fold(Duration::zero(), |total, day| total + *day);
+
+    let header = Paragraph::new(Spans::from(vec![
+        Span::styled(">> ", Style::default().add_modifier(Modifier::DIM)),
+        Span::styled(
+            format!(
+                "{} to {}",
+                util::format_day(&app.settings, first),
+                util::format_day(&app.settings, last)
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(", {} in total", util::format_duration(&app.settings, total)),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ]));
+    f.render_widget(header, chunks[0]);
+
+    draw_days(f, app, chunks[1], first, &daily);
+    draw_tags(f, app, chunks[2], first, days);
+}
+
+fn draw_days<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    area: Rect,
+    first: NaiveDate,
+    daily: &[Duration],
+) {
+    let labels: Vec<String> = (0..daily.len())
+        .map(|day| {
+            (first + Duration::days(day as i64))
+                .format("%d")
+                .to_string()
+        })
+        .collect();
+    let data: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(daily)
+        .map(|(label, duration)| (label.as_str(), minutes(*duration)))
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Hours per day");
+    let inner = block.inner(area);
+    let value_style = Style::default()
+        .bg(app.settings.theme.accent)
+        .add_modifier(Modifier::BOLD);
+    let chart = BarChart::default()
+        .block(block)
+        .data(&data)
+        .bar_width(BAR_WIDTH)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(app.settings.theme.accent))
+        .value_style(value_style)
+        .label_style(Style::default().add_modifier(Modifier::DIM));
+    f.render_widget(chart, area);
+    draw_hours(f, inner, daily, BAR_WIDTH, value_style);
+}
+
+fn draw_tags<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, first: NaiveDate, days: usize) {
+    let mut totals = app.state.daily_tags.totals(first, days, Local::now());
+    // From the most time spent, like tag reports
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Hours per tag");
+    if totals.is_empty() {
+        let paragraph = Paragraph::new(Span::styled(
+            "No time recorded in these days.",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+        .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    // Widest bars fitting all tags, dropping the least used ones if needed
+    let inner = area.width.saturating_sub(2) + 1;
+    let count = totals.len().min(usize::from(inner / (BAR_WIDTH + 1)));
+    let bar_width = (inner / count.max(1) as u16)
+        .saturating_sub(1)
+        .clamp(BAR_WIDTH, 12);
+    let totals = &totals[..count];
+    let data: Vec<(&str, u64)> = totals
+        .iter()
+        .map(|(name, duration)| (name.as_str(), minutes(*duration)))
+        .collect();
+    let durations: Vec<Duration> = totals.iter().map(|(_, duration)| *duration).collect();
+    let inner = block.inner(area);
+    let value_style = Style::default()
+        .bg(app.settings.theme.running)
+        .add_modifier(Modifier::BOLD);
+    let chart = BarChart::default()
+        .block(block)
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Style::default().fg(app.settings.theme.running))
+        .value_style(value_style)
+        .label_style(Style::default().add_modifier(Modifier::DIM));
+    f.render_widget(chart, area);
+    draw_hours(f, inner, &durations, bar_width, value_style);
+}
+
+/// Width of the sparklines column in an explorer of the given width, a day
+/// per column, or none if there is not enough room left for the names.
+pub fn sparklines_width(width: u16) -> u16 {
+    let days = SPARKLINE_DAYS.min(usize::from(width / 3));
+    if days < 5 {
+        return 0;
+    }
+    days as u16
+}
+
+/// Draws the time spent on each project during the last days, a row per
+/// project and a column per day.
+pub fn draw_project_sparklines<B: Backend>(
+    f: &mut Frame<B>,
+    totals: &DailyTotals<ProjectId>,
+    area: Rect,
+    projects: &[ProjectId],
+    style: Style,
+) {
+    let days = usize::from(area.width);
+    let now = Local::now();
+    let first = now.date_naive() - Duration::days(days as i64 - 1);
+    let rows = projects.iter().take(usize::from(area.height));
+    let data: Vec<Vec<u64>> = rows
+        .map(|project_id| {
+            totals
+                .daily(first, days, Some(project_id), now)
+                .into_iter()
+                .map(minutes)
+                .collect()
+        })
+        .collect();
+    // Shared maximum so projects can be compared
+    let max = data.iter().flatten().copied().max().unwrap_or(0).max(1);
+    for (row, data) in data.iter().enumerate() {
+        let rect = Rect::new(area.x, area.y + row as u16, area.width, 1);
+        let sparkline = Sparkline::default().data(data).max(max).style(style);
+        f.render_widget(sparkline, rect);
+    }
+}
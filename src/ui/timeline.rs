@@ -0,0 +1,367 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState};
+use tui::Frame;
+
+use crate::app::{App, Repository};
+use crate::model::{Entry, FromId, Project, Task};
+use crate::report;
+
+use super::util;
+
+/// Shorter pauses between entries are not shown as gaps.
+const MIN_GAP_MINUTES: i64 = 1;
+/// Rows of overlapping entries drawn at most.
+const MAX_LANES: usize = 4;
+
+#[derive(Default, Debug)]
+pub struct TimelineState {
+    /// Shown day, today when unset.
+    day: Option<NaiveDate>,
+    /// Index of the selected entry or gap.
+    pub selected: usize,
+}
+
+impl TimelineState {
+    pub fn day(&self) -> NaiveDate {
+        self.day.unwrap_or_else(|| Local::now().date_naive())
+    }
+
+    pub fn show(&mut self, day: NaiveDate) {
+        self.day = Some(day);
+        self.selected = 0;
+    }
+
+    /// Moves to another day, up to today.
+    pub fn shift(&mut self, days: i64) {
+        let today = Local::now().date_naive();
+        self.show((self.day() + Duration::days(days)).min(today));
+    }
+
+    pub fn today(&mut self) {
+        self.day = None;
+        self.selected = 0;
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self, count: usize) {
+        self.selected = (self.selected + 1).min(count.saturating_sub(1));
+    }
+}
+
+/// Part of a day on the timeline, either tracked or not.
+pub enum Slot<'a> {
+    Entry {
+        entry: &'a Entry,
+        /// Whether it starts before the previous entries end.
+        overlaps: bool,
+    },
+    Gap {
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    },
+}
+
+impl Slot<'_> {
+    pub fn start(&self) -> DateTime<Local> {
+        match self {
+            Self::Entry { entry, .. } => entry.start,
+            Self::Gap { start, .. } => *start,
+        }
+    }
+
+    pub fn end(&self, now: DateTime<Local>) -> DateTime<Local> {
+        match self {
+            Self::Entry { entry, .. } => entry.end.unwrap_or(now),
+            Self::Gap { end, .. } => *end,
+        }
+    }
+}
+
+/// Entries of a day with the gaps between them, from the earliest.
+pub fn slots(repository: &Repository, day: NaiveDate, now: DateTime<Local>) -> Vec<Slot<'_>> {
+    let mut slots = Vec::new();
+    let mut reached: Option<DateTime<Local>> = None;
+    for entry in report::day_entries(repository, day) {
+        let overlaps = match reached {
+            Some(reached) if entry.start - reached >= Duration::minutes(MIN_GAP_MINUTES) => {
+                slots.push(Slot::Gap {
+                    start: reached,
+                    end: entry.start,
+                });
+                false
+            }
+            Some(reached) => entry.start < reached,
+            None => false,
+        };
+        slots.push(Slot::Entry { entry, overlaps });
+        let end = entry.end.unwrap_or(now);
+        reached = Some(reached.map_or(end, |reached| reached.max(end)));
+    }
+    slots
+}
+
+fn task_label(repository: &Repository, entry: &Entry) -> String {
+    let task = Task::from_id(&entry.task_id, repository);
+    let project = Project::from_id(&task.project_id, repository);
+    format!("{}/{}", project.name, task.name)
+}
+
+pub fn draw_timeline<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    let state = &app.state.timeline;
+    let now = Local::now();
+    let day = state.day();
+    let slots = slots(&app.repository, day, now);
+    let lanes = lanes(&slots);
+    let lane_count = lanes.iter().max().map_or(1, |lane| lane + 1);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(lane_count as u16 + 3),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    let start = report::start_of_day(day);
+    let end = report::start_of_day(day + Duration::days(1));
+    let (tracked, gaps) = slots.iter().fold(
+        (Duration::zero(), Duration::zero()),
+        |(tracked, gaps), slot| match slot {
+            Slot::Entry { entry, .. } => (tracked + report::overlap(entry, start, end, now), gaps),
+            Slot::Gap { start, end } => (tracked, gaps + (*end - *start)),
+        },
+    );
+    let header = Paragraph::new(Spans::from(vec![
+        Span::styled(">> ", Style::default().add_modifier(Modifier::DIM)),
+        Span::styled(
+            util::format_day(&app.settings, day),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            format!(
+                ", {} tracked, {} in gaps",
+                util::format_duration(&app.settings, tracked),
+                util::format_duration(&app.settings, gaps)
+            ),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ]));
+    f.render_widget(header, chunks[0]);
+
+    if slots.is_empty() {
+        let paragraph = Paragraph::new(Span::styled(
+            "No time recorded on this day.",
+            Style::default().add_modifier(Modifier::DIM),
+        ))
+        .block(Block::default().borders(Borders::ALL).title("Timeline"));
+        f.render_widget(paragraph, chunks[1]);
+        return;
+    }
+    let selected = state.selected.min(slots.len() - 1);
+    draw_blocks(f, app, chunks[1], day, &slots, &lanes, selected);
+    draw_slots(f, app, chunks[2], &slots, selected);
+}
+
+/// Row of each slot, overlapping entries are moved down to the first free row.
+fn lanes(slots: &[Slot]) -> Vec<usize> {
+    let now = Local::now();
+    let mut ends: Vec<DateTime<Local>> = Vec::new();
+    slots
+        .iter()
+        .map(|slot| match slot {
+            Slot::Gap { .. } => 0,
+            Slot::Entry { entry, .. } => {
+                let end = entry.end.unwrap_or(now);
+                match ends.iter().position(|lane_end| *lane_end <= entry.start) {
+                    Some(lane) => {
+                        ends[lane] = end;
+                        lane
+                    }
+                    None if ends.len() < MAX_LANES => {
+                        ends.push(end);
+                        ends.len() - 1
+                    }
+                    None => MAX_LANES - 1,
+                }
+            }
+        })
+        .collect()
+}
+
+fn draw_blocks<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    area: Rect,
+    day: NaiveDate,
+    slots: &[Slot],
+    lanes: &[usize],
+    selected: usize,
+) {
+    let now = Local::now();
+    let block = Block::default().borders(Borders::ALL).title("Timeline");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let width = usize::from(inner.width);
+    if width == 0 {
+        return;
+    }
+
+    // Whole hours around the day's slots
+    let day_start = report::start_of_day(day);
+    let first = slots[0].start().max(day_start);
+    let last = slots
+        .iter()
+        .map(|slot| slot.end(now))
+        .max()
+        .unwrap_or(first);
+    let from = day_start + Duration::hours((first - day_start).num_minutes() / 60);
+    let hours = ((last - from).num_minutes() + 59) / 60;
+    let to = from + Duration::hours(hours.clamp(1, 24));
+    let span = (to - from).num_seconds() as f64;
+    let column = |time: DateTime<Local>| -> usize {
+        let offset = (time - from).num_seconds() as f64 / span;
+        ((offset * width as f64).round().max(0.0) as usize).min(width)
+    };
+
+    let mut axis = " ".repeat(width);
+    let mut free = 0;
+    let mut hour = from;
+    while hour < to {
+        let position = column(hour);
+        let label = hour.format("%H").to_string();
+        if position >= free && position + label.len() <= width {
+            axis.replace_range(position..position + label.len(), &label);
+            free = position + label.len() + 1;
+        }
+        hour += Duration::hours(1);
+    }
+    let mut lines = vec![Spans::from(Span::styled(
+        axis,
+        Style::default().add_modifier(Modifier::DIM),
+    ))];
+
+    let lane_count = lanes.iter().max().map_or(1, |lane| lane + 1);
+    for lane in 0..lane_count {
+        let mut spans = Vec::new();
+        let mut position = 0;
+        for (index, slot) in slots.iter().enumerate() {
+            if lanes[index] != lane {
+                continue;
+            }
+            let start = column(slot.start()).max(position);
+            let end = column(slot.end(now)).max(start + 1).min(width);
+            if start >= end {
+                continue;
+            }
+            if start > position {
+                spans.push(Span::from(" ".repeat(start - position)));
+            }
+            let length = end - start;
+            let (text, mut style) = match slot {
+                Slot::Entry { entry, overlaps } => {
+                    let label: String = format!("▏{}", task_label(&app.repository, entry))
+                        .chars()
+                        .chain(std::iter::repeat(' '))
+                        .take(length)
+                        .collect();
+                    let color = if *overlaps {
+                        app.settings.theme.error
+                    } else if entry.is_running() {
+                        app.settings.theme.running
+                    } else {
+                        app.settings.theme.accent
+                    };
+                    (label, Style::default().bg(color))
+                }
+                Slot::Gap { .. } => (
+                    "░".repeat(length),
+                    Style::default().fg(app.settings.theme.error),
+                ),
+            };
+            if index == selected {
+                style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+            }
+            spans.push(Span::styled(text, style));
+            position = end;
+        }
+        lines.push(Spans::from(spans));
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_slots<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    area: Rect,
+    slots: &[Slot],
+    selected: usize,
+) {
+    let now = Local::now();
+    let rows: Vec<Row> = slots
+        .iter()
+        .map(|slot| {
+            let end = match slot {
+                Slot::Entry { entry, .. } if entry.is_running() => {
+                    Cell::from("running").style(Style::default().fg(app.settings.theme.running))
+                }
+                _ => Cell::from(util::format_time(&app.settings, slot.end(now))),
+            };
+            let (label, style) = match slot {
+                Slot::Entry {
+                    entry,
+                    overlaps: true,
+                } => (
+                    format!("{} (overlaps)", task_label(&app.repository, entry)),
+                    Style::default().fg(app.settings.theme.error),
+                ),
+                Slot::Entry { entry, .. } => (task_label(&app.repository, entry), Style::default()),
+                Slot::Gap { .. } => (
+                    "gap".to_string(),
+                    Style::default().add_modifier(Modifier::DIM),
+                ),
+            };
+            Row::new(vec![
+                Cell::from(util::format_time(&app.settings, slot.start())),
+                end,
+                Cell::from(util::format_duration(
+                    &app.settings,
+                    slot.end(now) - slot.start(),
+                )),
+                Cell::from(label),
+            ])
+            .style(style)
+        })
+        .collect();
+    let header = Row::new(vec!["Start", "End", "Duration", "Task"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Length(8),
+        Constraint::Percentage(70),
+    ];
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Entries and gaps"),
+        )
+        .widths(&widths)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut table_state = TableState::default();
+    table_state.select(Some(selected));
+    f.render_stateful_widget(table, area, &mut table_state);
+
+    if let Some((position, rect)) = util::list_position(area, selected + 1, slots.len()) {
+        f.render_widget(position, rect);
+    }
+}
@@ -1,8 +1,12 @@
+use chrono::{DateTime, Duration, Local, NaiveDate};
 use tui::layout::{Alignment, Rect};
-use tui::style::{Color, Modifier, Style};
+use tui::style::{Modifier, Style};
 use tui::text::{Span, Spans};
 use tui::widgets::Paragraph;
 
+use crate::app::{Repository, Settings};
+use crate::model::{FromId, Tag, TagId};
+
 pub fn list_position<'a>(
     area: Rect,
     position: usize,
@@ -48,17 +52,13 @@ pub fn overlay(area: Rect, mut height: u16, footer: bool) -> (Rect, Rect, Option
     (area, clear, footer)
 }
 
-pub fn bindings<'a, B>(bindings: B) -> Paragraph<'a>
-where
-    B: Into<Vec<(&'a str, &'a str)>>,
-{
+pub fn bindings<'a>(settings: &Settings, bindings: Vec<(String, &str)>) -> Paragraph<'a> {
     let text = bindings
-        .into()
         .iter()
         .map(|(key, action)| format!("{}: {}", key, action))
         .collect::<Vec<String>>()
         .join(", ");
-    let style = Style::default().fg(Color::Blue);
+    let style = Style::default().fg(settings.theme.accent);
     Paragraph::new(text).style(style)
 }
 
@@ -70,3 +70,66 @@ pub fn default_list_item<'a>(value: &'a str, selected: bool) -> Spans<'a> {
     };
     Spans::from(vec![Span::styled(value, style)])
 }
+
+/// Splits a text in spans, styling the characters at the given indices.
+pub fn highlight(
+    text: String,
+    indices: &[usize],
+    style: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut current = String::new();
+    let mut highlighted = false;
+    for (index, ch) in text.chars().enumerate() {
+        let matched = indices.contains(&index);
+        if matched != highlighted && !current.is_empty() {
+            let style = if highlighted { highlight } else { style };
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        highlighted = matched;
+        current.push(ch);
+    }
+    if !current.is_empty() {
+        let style = if highlighted { highlight } else { style };
+        spans.push(Span::styled(current, style));
+    }
+    spans
+}
+
+/// Formats a duration, rounded according to the settings.
+pub fn format_duration(settings: &Settings, duration: Duration) -> String {
+    let minutes = settings.round(duration).num_minutes();
+    format!("{:02}h{:02}", minutes / 60, minutes % 60)
+}
+
+pub fn format_clock(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub fn format_date(settings: &Settings, date: DateTime<Local>) -> String {
+    date.format(&settings.date_format).to_string()
+}
+
+pub fn format_day(settings: &Settings, day: NaiveDate) -> String {
+    format!("{} {}", day.format("%a"), day.format(&settings.date_format))
+}
+
+pub fn format_time(settings: &Settings, time: DateTime<Local>) -> String {
+    time.format(&settings.time_format).to_string()
+}
+
+pub fn tag_names<'a>(repository: &'a Repository, tags: &'a [TagId]) -> Vec<&'a str> {
+    let mut names: Vec<&str> = tags
+        .iter()
+        .map(|id| Tag::from_id(id, repository).name.as_ref())
+        .collect();
+    names.sort_unstable();
+    names
+}